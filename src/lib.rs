@@ -1,8 +1,30 @@
 pub mod wizard {
+    pub mod advisor;
+    pub mod arena;
+    pub mod bot;
     pub mod card;
+    pub mod chat;
+    pub mod duplicate;
+    pub mod error;
+    pub mod external;
     pub mod game;
+    pub mod i18n;
+    pub mod lobby;
+    pub mod net;
     pub mod player;
+    pub mod profile;
+    pub mod rating;
+    pub mod record;
+    pub mod report;
     pub mod round;
+    pub mod rules;
+    pub mod simulation;
+    pub mod solver;
+    pub mod tournament;
+    pub mod tutorial;
+    pub mod view;
+    #[cfg(feature = "web")]
+    pub mod web;
 }
 
 mod tests;
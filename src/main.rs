@@ -1,30 +1,1346 @@
-use inquire::Select;
-use wizard::wizard::game::Game;
-use wizard::wizard::player::Player;
+use clap::{Args, Parser, Subcommand};
+use inquire::{Confirm, MultiSelect, Select, Text};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use wizard::wizard::arena::Arena;
+use wizard::wizard::bot::Difficulty;
+use wizard::wizard::chat::Emote;
+use wizard::wizard::duplicate::{DealSet, DuplicateResult};
+use wizard::wizard::error::WizardError;
+use wizard::wizard::external::{self, ExternalBot};
+use wizard::wizard::game::{Action, Game};
+use wizard::wizard::i18n::{self, Language};
+use wizard::wizard::lobby::{self, Lobby, LobbyClient, RoomInfo, RoomStatus};
+use wizard::wizard::net::{self, Client, ClientMessage, Server, ServerMessage, TimeoutPolicy};
+use wizard::wizard::player::{Player, PlayerId};
+use wizard::wizard::profile::{Profile, ProfileStore};
+use wizard::wizard::record::{Event, GameRecord};
+use wizard::wizard::report::Report;
+use wizard::wizard::round::Round;
+use wizard::wizard::rules::{RuleSet, Schedule};
+use wizard::wizard::simulation::Simulation;
+use wizard::wizard::solver::Solver;
+use wizard::wizard::tournament::{Scoring, Tournament};
+use wizard::wizard::tutorial::{Lesson, Task, LEARNER};
+use wizard::wizard::view::View;
+
+/// The card game Wizard in the terminal.
+#[derive(Parser)]
+#[command(name = "wizard")]
+struct Cli {
+    /// Plays a game at this computer if no command is given.
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The language of all text: en or de. Without it the environment variable WIZARD_LANG is used.
+    #[arg(long, global = true, value_parser = parse_language)]
+    lang: Option<Language>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Plays a game at this computer. Everything that is not given as a flag is asked.
+    Play {
+        #[command(flatten)]
+        options: GameOptions,
+        /// Saves the record of the game to the given file, see `replay`.
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
+    /// Hosts a game that other players join over the network with `join`.
+    Host {
+        #[command(flatten)]
+        options: GameOptions,
+        /// The amount of players who join over the network. They take the seats after the players of --players.
+        #[arg(long, default_value_t = 1)]
+        remote: usize,
+        #[arg(long, default_value = "0.0.0.0:7878")]
+        address: String,
+        #[command(flatten)]
+        server: ServerOptions,
+    },
+    /// Hosts a game that other players join in their browsers.
+    #[cfg(feature = "web")]
+    Web {
+        #[command(flatten)]
+        options: GameOptions,
+        /// The amount of players who join in a browser. They take the seats after the players of --players.
+        #[arg(long, default_value_t = 3)]
+        remote: usize,
+        #[arg(long, default_value = "0.0.0.0:8080")]
+        address: String,
+        #[command(flatten)]
+        server: ServerOptions,
+    },
+    /// Joins a game hosted with `host`.
+    Join {
+        /// The address of the host, e.g. 192.168.0.10:7878.
+        address: String,
+        /// Your name at the table. It is asked if not given.
+        #[arg(long)]
+        name: Option<String>,
+        /// The session token of a seat you lost the connection to. You take the seat again instead of a new one.
+        #[arg(long)]
+        token: Option<String>,
+        /// Asks on every turn whether you want to chat before your move.
+        #[arg(long)]
+        chat: bool,
+    },
+    /// Watches a game hosted with `host` without taking a seat.
+    Watch {
+        /// The address of the host, e.g. 192.168.0.10:7878.
+        address: String,
+        #[arg(long, default_value = "Spectator")]
+        name: String,
+    },
+    /// Runs a lobby where players open rooms with their own rules and play many games at the same time.
+    Lobby {
+        #[arg(long, default_value = "0.0.0.0:7879")]
+        address: String,
+    },
+    /// Enters a lobby started with `lobby` to open, join or watch its rooms.
+    Enter {
+        /// The address of the lobby, e.g. 192.168.0.10:7879.
+        address: String,
+        /// Your name at the table. It is asked if not given.
+        #[arg(long)]
+        name: Option<String>,
+        /// Asks on every turn whether you want to chat before your move.
+        #[arg(long)]
+        chat: bool,
+    },
+    /// Plays as the reference bot of the external bot protocol on stdin and stdout, see `check-bot`.
+    ReferenceBot,
+    /// Checks whether an external bot follows the protocol: it plays short games against bots and every crash,
+    /// timeout and illegal move is reported.
+    CheckBot {
+        /// The command that starts the bot, e.g. "python3 greedy.py".
+        command: String,
+        #[arg(long, default_value_t = 20)]
+        games: u32,
+        /// The milliseconds the bot has for every action.
+        #[arg(long, default_value_t = 1000)]
+        timeout: u64,
+    },
+    /// Plays many games between bots and shows how they did. External bots are given as "external:COMMAND".
+    Simulate {
+        #[command(flatten)]
+        options: GameOptions,
+        #[arg(long, default_value_t = 100)]
+        games: u32,
+    },
+    /// Plays a round robin between the bots of --players and ranks them. Every combination of bots plays every deal
+    /// in every seat rotation.
+    Arena {
+        #[command(flatten)]
+        options: GameOptions,
+        /// The amounts of players at a table, e.g. 3,4.
+        #[arg(long, default_value = "3", value_delimiter = ',')]
+        tables: Vec<usize>,
+        /// The deals every combination of bots plays.
+        #[arg(long, default_value_t = 10)]
+        deals: u32,
+        /// The milliseconds external bots have for every action.
+        #[arg(long, default_value_t = 1000)]
+        timeout: u64,
+        /// Ranks the bots by their placement at the table instead of their score.
+        #[arg(long)]
+        placement: bool,
+    },
+    /// Shows a saved game event by event.
+    Replay { file: PathBuf },
+    /// Shows for every round of a saved game how many tricks every player could force with perfect play.
+    Solve {
+        file: PathBuf,
+        /// Rounds with more cards are skipped, as they take long to solve. By default 8 for 3 players down to 4 for
+        /// 6 players.
+        #[arg(long)]
+        max_cards: Option<u32>,
+    },
+    /// Shows the statistics of all player profiles.
+    Stats,
+    /// Shows the rules of a rule set, or teaches them in a guided tutorial.
+    Rules {
+        /// One of the rule presets: standard, short, pyramid or lunch.
+        #[arg(long)]
+        rules: Option<String>,
+        /// The round schedule: full, odd, updown:N, random:N or a list such as 1,3,5,7,10.
+        #[arg(long)]
+        rounds: Option<String>,
+        /// Prints the rules as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Plays scripted hands that teach following suit, trumps, Wizards, Fools and bidding.
+        #[arg(long)]
+        tutorial: bool,
+    },
+    /// Plays a tournament over several sessions.
+    Tournament,
+    /// Plays the same deals at several tables. The deal set is loaded from the given file or generated.
+    Duplicate { file: Option<String> },
+}
+
+#[derive(Args, Default)]
+struct GameOptions {
+    /// The players in seating order, e.g. "Ann,Bob,bot:hard". Bots are easy, medium or hard.
+    #[arg(long)]
+    players: Option<String>,
+    /// The seed of the deals. Games with the same seed and players get the same cards.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// One of the rule presets: standard, short, pyramid or lunch.
+    #[arg(long)]
+    rules: Option<String>,
+    /// The round schedule: full, odd, updown:N, random:N or a list such as 1,3,5,7,10.
+    #[arg(long)]
+    rounds: Option<String>,
+    /// Humans can take back their guesses and cards.
+    #[arg(long)]
+    casual: bool,
+    /// Every human gets suggestions from the advisor.
+    #[arg(long)]
+    advisor: bool,
+}
+
+#[derive(Args)]
+struct ServerOptions {
+    /// Spectators are shown the hands of every round this many rounds later. Without it they never see them.
+    #[arg(long)]
+    reveal_hands: Option<u32>,
+    /// The seconds a remote player has for every guess or card. Without it the game waits as long as it takes.
+    #[arg(long)]
+    timeout: Option<u64>,
+    /// What happens when a remote player takes too long: auto plays a legal move, easy, medium or hard lets a bot
+    /// play until the player rejoins.
+    #[arg(long, default_value = "auto", value_parser = parse_timeout_policy)]
+    on_timeout: TimeoutPolicy,
+}
+
+fn parse_timeout_policy(s: &str) -> Result<TimeoutPolicy, String> {
+    match s {
+        "auto" => Ok(TimeoutPolicy::AutoPlay),
+        _ => Difficulty::from_name(s)
+            .map(TimeoutPolicy::Bot)
+            .ok_or_else(|| i18n::text("main.allowed_timeouts")),
+    }
+}
+
+fn parse_language(s: &str) -> Result<Language, String> {
+    Language::from_code(s).ok_or_else(|| i18n::text("main.allowed_languages"))
+}
+
+impl GameOptions {
+    /// Returns the rule set of --rules and --rounds, `None` if neither is given.
+    fn rule_set(&self) -> Result<Option<RuleSet>, WizardError> {
+        let mut rules = match &self.rules {
+            Some(name) => {
+                RuleSet::preset(name).ok_or_else(|| WizardError::UnknownRuleSet(name.clone()))?
+            }
+            None if self.rounds.is_some() => RuleSet::default(),
+            None => return Ok(None),
+        };
+        if let Some(rounds) = &self.rounds {
+            rules = RuleSet::custom(rounds.parse::<Schedule>()?);
+        }
+        Ok(Some(rules))
+    }
+
+    /// Returns a game with the players of --players and all other flags applied.
+    fn game(&self, players: Vec<Player>) -> Result<Game, WizardError> {
+        // external bots need `external::play`, which doesn't ask humans
+        if let Some(player) = players.iter().find(|e| e.external.is_some()) {
+            return Err(WizardError::InvalidPlayer(player.name.clone()));
+        }
+        let mut game = Game {
+            players,
+            undo: self.casual,
+            rules: self.rule_set()?.unwrap_or_default(),
+            ..Game::default()
+        };
+        for player in &mut game.players {
+            player.advisor = self.advisor && player.bot.is_none();
+        }
+        Ok(game)
+    }
+
+    /// Sets the deal set of the given game if there is a seed. The players have to be seated and the rules chosen.
+    fn deal(&self, game: &mut Game) -> Result<(), WizardError> {
+        if let Some(seed) = self.seed {
+            let player_count = game.players.len() as u32;
+            game.deal_set = Some(DealSet::generate(seed, player_count, &game.rules.schedule)?);
+        }
+        Ok(())
+    }
+}
 
 fn main() {
+    match run() {
+        Ok(()) => {}
+        // Esc or Ctrl-C in a prompt
+        Err(WizardError::InputCancelled) => println!("{}", i18n::text("main.cancelled")),
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run() -> Result<(), WizardError> {
+    // the argument parser already speaks the language of the environment
+    if let Some(language) = Language::from_env() {
+        i18n::set_language(language);
+    }
+    let cli = Cli::parse();
+    if let Some(language) = cli.lang {
+        i18n::set_language(language);
+    }
+
+    let store_path = ProfileStore::default_path();
+    let mut store = match &store_path {
+        Some(path) => ProfileStore::load(path).unwrap_or_else(|e| {
+            println!("{}", i18n::text_with("main.profiles_not_loaded", &[&e]));
+            ProfileStore::default()
+        }),
+        None => ProfileStore::default(),
+    };
+
+    match cli.command {
+        None => play(&mut store, &store_path, &GameOptions::default(), None),
+        Some(Command::Play { options, save }) => play(&mut store, &store_path, &options, save),
+        Some(Command::Host {
+            options,
+            remote,
+            address,
+            server,
+        }) => host(
+            &mut store,
+            &store_path,
+            &options,
+            remote,
+            &address,
+            false,
+            &server,
+        ),
+        #[cfg(feature = "web")]
+        Some(Command::Web {
+            options,
+            remote,
+            address,
+            server,
+        }) => host(
+            &mut store,
+            &store_path,
+            &options,
+            remote,
+            &address,
+            true,
+            &server,
+        ),
+        Some(Command::Join {
+            address,
+            name,
+            token,
+            chat,
+        }) => join(&address, name, token, chat),
+        Some(Command::Watch { address, name }) => watch(&address, &name),
+        Some(Command::Lobby { address }) => {
+            let listener = TcpListener::bind(&address)?;
+            println!("{}", i18n::text_with("lobby.is_open", &[&address]));
+            Lobby::new(store, store_path).serve(move || net::accept(&listener));
+            Ok(())
+        }
+        Some(Command::Enter {
+            address,
+            name,
+            chat,
+        }) => enter(&address, name, chat),
+        Some(Command::ReferenceBot) => external::reference_bot(io::stdin().lock(), io::stdout()),
+        Some(Command::CheckBot {
+            command,
+            games,
+            timeout,
+        }) => {
+            let start = || {
+                let mut bot = ExternalBot::spawn(&command)?;
+                bot.timeout = Duration::from_millis(timeout);
+                Ok(bot)
+            };
+            let conformance = external::check(start, games)?;
+            print!("{}", conformance.text());
+            if !conformance.passed() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some(Command::Simulate { options, games }) => simulate(&options, games),
+        Some(Command::Arena {
+            options,
+            tables,
+            deals,
+            timeout,
+            placement,
+        }) => {
+            let mut arena = Arena::new(Player::parse_list(
+                options
+                    .players
+                    .as_deref()
+                    .unwrap_or("bot:easy,bot:medium,bot:hard"),
+            )?);
+            if let Some(human) = arena
+                .bots
+                .iter()
+                .find(|e| e.bot.is_none() && e.external.is_none())
+            {
+                return Err(WizardError::InvalidPlayer(human.name.clone()));
+            }
+            arena.rules = options.rule_set()?.unwrap_or_default();
+            arena.table_sizes = tables;
+            arena.deals = deals;
+            arena.seed = options.seed.unwrap_or_else(rand::random);
+            arena.timeout = Duration::from_millis(timeout);
+            if placement {
+                arena.scoring = Scoring::Placement;
+            }
+            println!(
+                "{}",
+                i18n::text_with(
+                    "arena.playing",
+                    &[&arena.game_count(), &arena.seed, &arena.rules.description()]
+                )
+            );
+            print!("{}", Arena::standings_text(&arena.run()?));
+            Ok(())
+        }
+        Some(Command::Replay { file }) => replay(&file),
+        Some(Command::Solve { file, max_cards }) => solve(&file, max_cards),
+        Some(Command::Stats) => {
+            print!("{}", store.stats());
+            Ok(())
+        }
+        Some(Command::Rules {
+            rules,
+            rounds,
+            json,
+            tutorial,
+        }) => {
+            if tutorial {
+                return play_tutorial();
+            }
+            let options = GameOptions {
+                rules,
+                rounds,
+                ..GameOptions::default()
+            };
+            let rules = options.rule_set()?.unwrap_or_default();
+            if json {
+                println!("{}", rules.to_json());
+            } else {
+                print!("{}", rules.summary());
+                println!("\n{}", i18n::text("rules.tutorial_hint"));
+            }
+            Ok(())
+        }
+        Some(Command::Tournament) => play_tournament(&mut store, &store_path),
+        Some(Command::Duplicate { file }) => play_duplicate(&mut store, &store_path, file),
+    }
+}
+
+/// Plays a game at this computer. Without --players the players, the advisor, casual play and the rules are asked.
+fn play(
+    store: &mut ProfileStore,
+    store_path: &Option<PathBuf>,
+    options: &GameOptions,
+    save: Option<PathBuf>,
+) -> Result<(), WizardError> {
     println!("Wizard-rs");
 
-    let mut game = Game::default();
+    let mut game = match &options.players {
+        Some(list) => options.game(Player::parse_list(list)?)?,
+        None => {
+            let mut game = options.game(select_players(store, 3..=6)?)?;
+
+            // select players who want suggestions
+            let humans: Vec<usize> = (0..game.players.len())
+                .filter(|i| game.players[*i].bot.is_none())
+                .collect();
+            if !humans.is_empty() && !options.advisor {
+                let names = humans
+                    .iter()
+                    .map(|i| game.players[*i].name.clone())
+                    .collect();
+                let selected =
+                    MultiSelect::new(&i18n::text("play.ask_advisor"), names).raw_prompt()?;
+
+                for option in selected {
+                    game.players[humans[option.index]].advisor = true;
+                }
+            }
+            if !humans.is_empty() && !options.casual {
+                // ranked games can't be taken back
+                game.undo = Confirm::new(&i18n::text("play.ask_casual"))
+                    .with_default(true)
+                    .prompt()?;
+            }
+            if options.rule_set()?.is_none() {
+                game.rules = select_rules(game.players.len())?;
+            }
+            game
+        }
+    };
+    options.deal(&mut game)?;
+
+    game.play()?;
+
+    if let Some(path) = save {
+        match game.record.save(&path) {
+            Ok(()) => println!("{}", i18n::text_with("play.saved_game", &[&path.display()])),
+            Err(e) => println!(
+                "{}",
+                i18n::text_with("main.not_saved", &[&path.display(), &e])
+            ),
+        }
+    }
+    finish_game(&game, store, store_path)
+}
+
+/// Updates the profiles and shows the final scores and the post-game report.
+fn finish_game(
+    game: &Game,
+    store: &mut ProfileStore,
+    store_path: &Option<PathBuf>,
+) -> Result<(), WizardError> {
+    // update player profiles
+    store.record_game(&game.record);
+    save_store(store, store_path);
+
+    // final scores
+    let mut players = game.players.clone();
+    players.sort_by_key(|e| -game.score(e.id));
+    println!("{}", i18n::text("main.final_scores"));
+    for (place, player) in players.iter().enumerate() {
+        let score = game.score(player.id);
+        match store.player_profile(player) {
+            Some(profile) => println!(
+                "{}. {}: {} ({})",
+                place + 1,
+                player.name,
+                score,
+                i18n::text_with("main.rating", &[&format!("{:.0}", profile.rating)])
+            ),
+            None => println!("{}. {}: {}", place + 1, player.name, score),
+        }
+    }
+
+    // post-game report
+    let report = Report::new(&game.record);
+    println!("{}", report.to_markdown());
+    if game.players.iter().all(|e| e.bot.is_some()) {
+        return Ok(());
+    }
+    let save = Confirm::new(&i18n::text("play.ask_save_report"))
+        .with_default(false)
+        .prompt()?;
+    if save {
+        for (path, content) in [
+            ("wizard-report.md", report.to_markdown()),
+            ("wizard-report.json", report.to_json()),
+        ] {
+            match fs::write(path, content) {
+                Ok(()) => println!("{}", i18n::text_with("main.saved", &[&path])),
+                Err(e) => println!("{}", i18n::text_with("main.not_saved", &[&path, &e])),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Waits for the remote players and plays the game with them. The players of --players sit at this computer.
+/// With `web` the remote players join in their browsers instead of with `join`. Anyone else who connects may watch.
+fn host(
+    store: &mut ProfileStore,
+    store_path: &Option<PathBuf>,
+    options: &GameOptions,
+    remote: usize,
+    address: &str,
+    web: bool,
+    server_options: &ServerOptions,
+) -> Result<(), WizardError> {
+    let players = match &options.players {
+        Some(list) => Player::parse_list(list)?,
+        None => Vec::new(),
+    };
+    let game = options.game(players)?;
+    let seats: Vec<usize> = (game.players.len()..game.players.len() + remote).collect();
+    let player_count = game.players.len() + remote;
+    if !(3..=6).contains(&player_count) {
+        return Err(WizardError::InvalidPlayerCount(player_count));
+    }
+
+    let listener = TcpListener::bind(address)?;
+    let mut server = match web {
+        #[cfg(feature = "web")]
+        true => {
+            println!(
+                "{}",
+                i18n::text_with("host.waiting_web", &[&remote, &listener.local_addr()?])
+            );
+            let server = wizard::wizard::web::host(&listener, game, &seats)?;
+            let listener = listener.try_clone()?;
+            server.admit(move || wizard::wizard::web::accept(&listener));
+            server
+        }
+        _ => {
+            println!(
+                "{}",
+                i18n::text_with("host.waiting", &[&remote, &listener.local_addr()?])
+            );
+            let server = Server::host(&listener, game, &seats)?;
+            let listener = listener.try_clone()?;
+            server.admit(move || net::accept(&listener));
+            server
+        }
+    };
+    server.reveal_hands = server_options.reveal_hands;
+    server.timeout = server_options.timeout.map(Duration::from_secs);
+    server.on_timeout = server_options.on_timeout;
+    options.deal(&mut server.game)?;
+    server.play()?;
+
+    finish_game(&server.game, store, store_path)
+}
+
+/// Joins a hosted game, or takes the seat of the token again, and plays it in the terminal. A lost connection is
+/// restored a few times before giving up.
+fn join(
+    address: &str,
+    name: Option<String>,
+    token: Option<String>,
+    chat: bool,
+) -> Result<(), WizardError> {
+    let client = match token {
+        Some(token) => Client::rejoin(address, &token)?,
+        None => {
+            let name = match name {
+                Some(name) => name,
+                None => Player::new(1)?.name,
+            };
+            Client::join(address, &name)?
+        }
+    };
+    println!("{}", i18n::text_with("join.joined", &[&address]));
+    play_remote(client, address, chat)
+}
+
+/// Plays the seat of the client in the terminal until the game is over. With `chat` the player is asked on every
+/// turn whether they want to chat first.
+fn play_remote(mut client: Client, address: &str, chat: bool) -> Result<(), WizardError> {
+    let token = client.token.clone().unwrap_or_default();
+    println!(
+        "{}",
+        i18n::text_with(
+            "join.token",
+            &[&format!("wizard join {} --token {}", address, token)]
+        )
+    );
+
+    let mut show = |client: &Client, message: &ServerMessage| match message {
+        ServerMessage::Events { events } => {
+            for event in events {
+                for line in event.describe(&client.players) {
+                    println!("{}", line);
+                }
+            }
+        }
+        ServerMessage::TimedOut { action } => {
+            let chosen = match action {
+                Action::Guess(stitches) => i18n::text_with("game.guess_of", &[stitches]),
+                Action::Play(card) => card.name(),
+                Action::ChooseColor(color) => color.name(),
+                action => format!("{:?}", action),
+            };
+            println!("{}", i18n::text_with("join.timed_out", &[&chosen]))
+        }
+        ServerMessage::Rejected { reason } => println!("{}", reason),
+        _ => {}
+    };
+    let scores = loop {
+        let choose = |view: &View| match chat {
+            true => choose_or_chat(view),
+            false => choose_action(view).map(ClientMessage::from),
+        };
+        let error = match client.play(choose, &mut show) {
+            Ok(scores) => break scores,
+            Err(WizardError::Network(message)) => WizardError::Network(message),
+            Err(e) => return Err(e),
+        };
+
+        println!("{} {}", error, i18n::text("join.reconnecting"));
+        client = (1..=5)
+            .find_map(|_| {
+                thread::sleep(Duration::from_secs(2));
+                Client::rejoin(address, &token).ok()
+            })
+            .ok_or(error)?;
+    };
+
+    println!("{}", i18n::text("main.final_scores"));
+    for (name, score) in scores {
+        println!("{}: {}", name, score);
+    }
+    Ok(())
+}
+
+/// Watches a hosted game in the terminal.
+fn watch(address: &str, name: &str) -> Result<(), WizardError> {
+    let client = Client::watch(address, name)?;
+    println!("{}", i18n::text_with("watch.watching", &[&address]));
+    watch_remote(client)
+}
+
+/// Shows the game the client watches in the terminal until it is over.
+fn watch_remote(mut client: Client) -> Result<(), WizardError> {
+    let scores = client.play(
+        |view| Err::<Action, _>(WizardError::WrongPhase(view.phase)),
+        |client, message| match message {
+            ServerMessage::Events { events } => {
+                for event in events {
+                    for line in event.describe(&client.players) {
+                        println!("{}", line);
+                    }
+                }
+            }
+            ServerMessage::Hands { round, hands } => {
+                println!("{}", i18n::text_with("watch.hands", &[round]));
+                for (id, hand) in hands {
+                    let cards: Vec<String> = hand.iter().map(|e| e.name()).collect();
+                    println!(
+                        "{}",
+                        i18n::text_with("event.cards", &[&client.name(*id), &cards.join(", ")])
+                    );
+                }
+            }
+            _ => {}
+        },
+    )?;
+
+    println!("{}", i18n::text("main.final_scores"));
+    for (name, score) in scores {
+        println!("{}: {}", name, score);
+    }
+    Ok(())
+}
+
+/// Lists, opens, joins and watches the rooms of a lobby until the player takes a seat or watches a game.
+fn enter(address: &str, name: Option<String>, chat: bool) -> Result<(), WizardError> {
+    let mut lobby = LobbyClient::connect(address)?;
+    let mut rooms = lobby.rooms()?;
+    loop {
+        show_rooms(&rooms);
+        let options = [
+            "lobby.refresh",
+            "lobby.open",
+            "lobby.take_seat",
+            "lobby.watch",
+        ]
+        .iter()
+        .map(|e| i18n::text(e))
+        .collect();
+        let selected = Select::new(&i18n::text("lobby.ask_action"), options).raw_prompt()?;
+        let result = match selected.index {
+            0 => lobby.rooms(),
+            1 => {
+                let name = Text::new(&i18n::text("lobby.ask_room_name")).prompt()?;
+                let seats = select_number(&i18n::text("lobby.ask_seats"), 3..=6)?;
+                let rules = select_rules(seats as usize)?;
+                lobby.create(&name, rules, seats as usize)
+            }
+            2 => {
+                let Some(room) = select_room(&rooms, true)? else {
+                    continue;
+                };
+                let name = match &name {
+                    Some(name) => name.clone(),
+                    None => Player::new(1)?.name,
+                };
+                let client = lobby.join(room, &name)?;
+                println!("{}", i18n::text_with("lobby.seated", &[&room]));
+                return prepare_room(client, room, address, chat);
+            }
+            _ => {
+                let Some(room) = select_room(&rooms, false)? else {
+                    continue;
+                };
+                let name = name
+                    .clone()
+                    .unwrap_or_else(|| i18n::text("lobby.spectator"));
+                let client = lobby.watch(room, &name)?;
+                println!("{}", i18n::text_with("lobby.watching", &[&room]));
+                return watch_remote(client);
+            }
+        };
+        match result {
+            Ok(list) => rooms = list,
+            Err(WizardError::Room(reason)) => println!("{}", reason),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn show_rooms(rooms: &[RoomInfo]) {
+    if rooms.is_empty() {
+        println!("{}", i18n::text("lobby.no_rooms"));
+    }
+    for room in rooms {
+        let status = match &room.status {
+            RoomStatus::Waiting => {
+                i18n::text_with("lobby.waiting", &[&room.players.len(), &room.seats])
+            }
+            RoomStatus::Playing => i18n::text("lobby.playing"),
+            RoomStatus::Finished { .. } => i18n::text("lobby.finished"),
+            RoomStatus::Aborted { reason } => i18n::text_with("lobby.aborted", &[reason]),
+        };
+        let players: Vec<String> = room
+            .players
+            .iter()
+            .zip(&room.ratings)
+            .map(|(name, rating)| {
+                i18n::text_with("player.profile", &[name, &format!("{:.0}", rating)])
+            })
+            .collect();
+        println!(
+            "{}: {} ({}), {}. {}",
+            room.id,
+            room.name,
+            room.rules.description(),
+            status,
+            players.join(", ")
+        );
+    }
+}
 
-    // select player count
+/// Asks for a room, only waiting rooms if `waiting` is set. `None` is returned if there is no such room.
+fn select_room(rooms: &[RoomInfo], waiting: bool) -> Result<Option<u32>, WizardError> {
+    let rooms: Vec<&RoomInfo> = rooms
+        .iter()
+        .filter(|e| !waiting || e.status == RoomStatus::Waiting)
+        .collect();
+    if rooms.is_empty() {
+        println!("{}", i18n::text("lobby.no_room_to_choose"));
+        return Ok(None);
+    }
+    let options = rooms
+        .iter()
+        .map(|e| format!("{}: {}", e.id, e.name))
+        .collect();
+    let selected = Select::new(&i18n::text("lobby.ask_room"), options).raw_prompt()?;
+    Ok(Some(rooms[selected.index].id))
+}
+
+/// Adds bots to the room of the seated client until the game is started, then plays it.
+fn prepare_room(
+    mut client: Client,
+    room: u32,
+    address: &str,
+    chat: bool,
+) -> Result<(), WizardError> {
+    loop {
+        let options = ["lobby.add_bot", "lobby.start", "lobby.wait"]
+            .iter()
+            .map(|e| i18n::text(e))
+            .collect();
+        let selected = Select::new(&i18n::text("lobby.room_waiting"), options).raw_prompt()?;
+        let message = match selected.index {
+            0 => {
+                let difficulties = Difficulty::all_difficulties();
+                let names = difficulties.iter().map(|e| e.name()).collect();
+                let difficulty =
+                    Select::new(&i18n::text("lobby.ask_difficulty"), names).raw_prompt()?;
+                ClientMessage::AddBot {
+                    room,
+                    difficulty: difficulties[difficulty.index],
+                }
+            }
+            1 => ClientMessage::StartGame { room },
+            _ => break,
+        };
+        let start = matches!(message, ClientMessage::StartGame { .. });
+        match lobby::request(&mut client.connection, message) {
+            Ok(rooms) => {
+                show_rooms(&rooms);
+                if start {
+                    break;
+                }
+            }
+            Err(WizardError::Room(reason)) => println!("{}", reason),
+            Err(e) => return Err(e),
+        }
+    }
+    play_remote(client, address, chat)
+}
+
+/// Asks whether the player wants to make their move or say something first.
+fn choose_or_chat(view: &View) -> Result<ClientMessage, WizardError> {
+    let options = ["chat.move", "chat.say", "chat.emote"]
+        .iter()
+        .map(|e| i18n::text(e))
+        .collect();
+    match Select::new(&i18n::text("chat.your_turn"), options)
+        .raw_prompt()?
+        .index
+    {
+        0 => Ok(choose_action(view)?.into()),
+        1 => {
+            let text = Text::new(&i18n::text("chat.ask_message")).prompt()?;
+            Ok(ClientMessage::Chat { text })
+        }
+        _ => {
+            let emotes = Emote::all_emotes();
+            let texts = emotes.iter().map(|e| e.text()).collect();
+            let selected = Select::new(&i18n::text("chat.ask_emote"), texts).raw_prompt()?;
+            Ok(ClientMessage::Emote {
+                emote: emotes[selected.index],
+            })
+        }
+    }
+}
+
+/// Asks the player of the view to choose one of its options.
+fn choose_action(view: &View) -> Result<Action, WizardError> {
+    let player = view
+        .players
+        .iter()
+        .find(|e| Some(e.id) == view.viewer)
+        .cloned()
+        .unwrap_or_else(|| Player::with_name(i18n::text("player.you")));
+    let cards: Vec<String> = view.hand.iter().map(|e| e.name()).collect();
+    println!(
+        "{}",
+        i18n::text_with("event.cards", &[&player.name, &cards.join(", ")])
+    );
+
+    match view.options.first() {
+        Some(Action::ChooseColor(_)) => Ok(Action::ChooseColor(player.choose_color()?)),
+        Some(Action::Guess(_)) => {
+            let options = view
+                .options
+                .iter()
+                .filter_map(|e| match e {
+                    Action::Guess(stitches) => Some(*stitches),
+                    _ => None,
+                })
+                .collect();
+            Ok(Action::Guess(player.guess_stitches(options)?))
+        }
+        _ => {
+            let trick: Vec<String> = view
+                .trick
+                .iter()
+                .map(|(id, card)| format!("{} ({})", card.name(), view.name(*id)))
+                .collect();
+            println!(
+                "{}",
+                i18n::text_with("game.trick_so_far", &[&trick.join(", ")])
+            );
+            let options = view
+                .options
+                .iter()
+                .filter_map(|e| match e {
+                    Action::Play(card) => Some(*card),
+                    _ => None,
+                })
+                .collect();
+            Ok(Action::Play(player.play_card(options, None)?))
+        }
+    }
+}
+
+/// Plays games between the bots of --players, by default one bot of every difficulty.
+fn simulate(options: &GameOptions, games: u32) -> Result<(), WizardError> {
+    let list = options
+        .players
+        .as_deref()
+        .unwrap_or("bot:easy,bot:medium,bot:hard");
+    let players = Player::parse_list(list)?;
+    if let Some(human) = players
+        .iter()
+        .find(|e| e.bot.is_none() && e.external.is_none())
     {
-        let options = (3..=6).map(|e| e.to_string()).collect();
-        let player_count = Select::new("How many players are you?", options)
-            .prompt()
-            .unwrap()
-            .trim()
-            .parse::<i32>()
-            .unwrap();
+        return Err(WizardError::InvalidPlayer(human.name.clone()));
+    }
+
+    let simulation = Simulation {
+        players,
+        rules: options.rule_set()?.unwrap_or_default(),
+        seed: options.seed.unwrap_or_else(rand::random),
+        games,
+    };
+    println!(
+        "{}",
+        i18n::text_with(
+            "main.simulating",
+            &[&games, &simulation.seed, &simulation.rules.description()]
+        )
+    );
+    let records = simulation.run()?;
+    print!(
+        "{}",
+        Simulation::results_text(&simulation.results(&records))
+    );
+    Ok(())
+}
+
+/// Shows every event of a saved game and the final scores.
+fn replay(path: &Path) -> Result<(), WizardError> {
+    let record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(e) => {
+            println!(
+                "{}",
+                i18n::text_with("replay.not_loaded", &[&path.display(), &e])
+            );
+            return Ok(());
+        }
+    };
+
+    let players = record.players();
+    for event in &record.events {
+        for line in event.describe(&players) {
+            println!("{}", line);
+        }
+    }
+
+    println!("{}", i18n::text("main.final_scores"));
+    let scores = record.scores();
+    for player in &players {
+        println!("{}: {}", player.name, scores.get(&player.id).unwrap_or(&0));
+    }
+    Ok(())
+}
+
+/// Solves every round of a saved game with all hands known and compares the result with the guesses and tricks.
+fn solve(path: &Path, max_cards: Option<u32>) -> Result<(), WizardError> {
+    let record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(e) => {
+            println!(
+                "{}",
+                i18n::text_with("replay.not_loaded", &[&path.display(), &e])
+            );
+            return Ok(());
+        }
+    };
+
+    let mut solutions = Vec::new();
+    let mut guesses = BTreeMap::new();
+    let mut stitches: BTreeMap<PlayerId, u32> = BTreeMap::new();
+    for event in &record.events {
+        match event {
+            Event::RoundStarted {
+                round,
+                hands,
+                trump_card,
+                main_color,
+            } => {
+                guesses.clear();
+                stitches.clear();
+                let cards = hands.first().map_or(0, |e| e.1.len() as u32);
+                solutions = if hands.iter().any(|e| e.1.is_empty()) {
+                    println!("{}", i18n::text_with("solve.not_recorded", &[round]));
+                    Vec::new()
+                } else if cards > max_cards.unwrap_or(Solver::max_cards(hands.len())) {
+                    println!("{}", i18n::text_with("solve.skipped", &[round, &cards]));
+                    Vec::new()
+                } else {
+                    println!("{}", i18n::text_with("solve.round", &[round]));
+                    // the hands are listed in bidding order, so the last player dealt
+                    let dealer = hands.last().map_or(PlayerId(0), |e| e.0);
+                    let round =
+                        Round::new(*round, hands.clone(), dealer, *trump_card, *main_color)?;
+                    Solver::new(&round).analyse_all()
+                };
+            }
+            Event::Guessed { player, stitches } => {
+                guesses.insert(*player, *stitches);
+            }
+            Event::TrickWon { player } => {
+                *stitches.entry(*player).or_default() += 1;
+            }
+            Event::RoundScored { .. } => {
+                for solution in &solutions {
+                    let text = i18n::text_with(
+                        "solve.solution",
+                        &[
+                            &record.name(solution.player),
+                            &guesses.get(&solution.player).copied().unwrap_or(0),
+                            &stitches.get(&solution.player).copied().unwrap_or(0),
+                            &solution.max_tricks,
+                            &solution.min_tricks,
+                        ],
+                    );
+                    println!("  {}", text);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Teaches the rules with the lessons of the tutorial. Every lesson is repeated until the learner solves it.
+fn play_tutorial() -> Result<(), WizardError> {
+    println!("{}", i18n::text("tutorial.welcome"));
+    let lessons = Lesson::all_lessons();
+    for (number, lesson) in lessons.iter().enumerate() {
+        println!(
+            "\n{}",
+            i18n::text_with(
+                "tutorial.lesson",
+                &[&(number + 1), &lessons.len(), &lesson.title]
+            )
+        );
+        println!("{}", lesson.text);
+
+        let round = &lesson.round;
+        match round.trump_card {
+            Some(card) => println!("{}", i18n::text_with("tutorial.turned_up", &[&card.name()])),
+            None => println!("{}", i18n::text("tutorial.no_main_color")),
+        }
+        for (player, card) in round.playing_order().iter().zip(&round.trick) {
+            println!(
+                "{}",
+                i18n::text_with("tutorial.played", &[&Lesson::name(*player), &card.name()])
+            );
+        }
+        let hand = &round.hands[&LEARNER];
+        let names: Vec<String> = hand.iter().map(|e| e.name()).collect();
+
+        loop {
+            let feedback = match lesson.task {
+                Task::Guess(_) => {
+                    println!(
+                        "{}",
+                        i18n::text_with("tutorial.your_cards", &[&names.join(", ")])
+                    );
+                    let guess = select_number(&i18n::text("tutorial.ask_guess"), 0..=round.cards)?;
+                    lesson.try_guess(guess)
+                }
+                task => {
+                    let message = if task == Task::WinTrick {
+                        i18n::text("tutorial.ask_win")
+                    } else {
+                        i18n::text("tutorial.ask_lose")
+                    };
+                    let selected = Select::new(&message, names.clone()).raw_prompt()?;
+                    lesson.try_card(hand[selected.index])
+                }
+            };
+            println!("{}", feedback.text);
+            if feedback.passed {
+                break;
+            }
+        }
+    }
+    println!("\n{}", i18n::text("tutorial.done"));
+    Ok(())
+}
+
+/// Asks for the amount of players and bots and creates them. Humans select their profiles, bots their difficulty.
+fn select_players(
+    store: &ProfileStore,
+    player_counts: std::ops::RangeInclusive<u32>,
+) -> Result<Vec<Player>, WizardError> {
+    let mut players: Vec<Player> = Vec::new();
+
+    let player_count = select_number(&i18n::text("setup.ask_players"), player_counts)?;
+
+    let bot_count = select_number(&i18n::text("setup.ask_bots"), 0..=player_count)?;
+
+    // create players
+    for i in 1..=player_count - bot_count {
+        let available: Vec<Profile> = store
+            .profiles
+            .iter()
+            .filter(|e| e.bot.is_none())
+            .cloned()
+            .collect();
+        let player = Player::select(i, &available, &players)?;
+        players.push(player);
+    }
+
+    // create bots
+    for i in 1..=bot_count {
+        let options = Difficulty::all_difficulties()
+            .iter()
+            .map(|e| e.name())
+            .collect();
+        let selected = Select::new(
+            i18n::text_with("setup.ask_difficulty", &[&i]).as_str(),
+            options,
+        )
+        .raw_prompt()?;
+        let difficulty = Difficulty::all_difficulties()[selected.index];
+
+        let number = players.iter().filter(|e| e.bot == Some(difficulty)).count() as u32 + 1;
+        players.push(Player::bot(difficulty, number));
+    }
 
-        // create players
-        for i in 1..=player_count {
-            let player = Player::new(i as u32);
-            game.players.push(player);
+    Ok(players)
+}
+
+/// Asks the given question with the numbers of the range as options.
+fn select_number(
+    message: &str,
+    options: std::ops::RangeInclusive<u32>,
+) -> Result<u32, WizardError> {
+    let numbers: Vec<u32> = options.collect();
+    let selected =
+        Select::new(message, numbers.iter().map(|e| e.to_string()).collect()).raw_prompt()?;
+    Ok(numbers[selected.index])
+}
+
+/// Asks for one of the rule presets or a custom round schedule, which is checked against the given amount of players.
+fn select_rules(player_count: usize) -> Result<RuleSet, WizardError> {
+    let presets = RuleSet::presets();
+    let mut options: Vec<String> = presets.iter().map(|e| e.description()).collect();
+    options.push(i18n::text("setup.custom_schedule"));
+
+    let selected = Select::new(&i18n::text("setup.ask_rules"), options).raw_prompt()?;
+    if selected.index < presets.len() {
+        return Ok(presets[selected.index].clone());
+    }
+
+    loop {
+        let input = Text::new(&i18n::text("setup.ask_schedule")).prompt()?;
+        let schedule = input
+            .parse::<Schedule>()
+            .and_then(|e| e.rounds(player_count, &mut rand::thread_rng()).map(|_| e));
+        match schedule {
+            Ok(schedule) => return Ok(RuleSet::custom(schedule)),
+            Err(e) => println!("{}", e),
         }
     }
+}
 
-    for round_number in 1..=game.round_limit() {
-        println!("Round number {}", round_number);
+fn save_store(store: &ProfileStore, store_path: &Option<PathBuf>) {
+    if let Some(path) = store_path {
+        if let Err(e) = store.save(path) {
+            println!("{}", i18n::text_with("main.profiles_not_saved", &[&e]));
+        }
     }
-}
\ No newline at end of file
+}
+
+fn play_tournament(
+    store: &mut ProfileStore,
+    store_path: &Option<PathBuf>,
+) -> Result<(), WizardError> {
+    println!("{}", i18n::text("tournament.title"));
+
+    let roster = select_players(store, 3..=36)?;
+
+    let session_count = select_number(&i18n::text("tournament.ask_sessions"), 1..=10)?;
+
+    let scorings = Scoring::all_scorings();
+    let selected = Select::new(
+        &i18n::text("tournament.ask_scoring"),
+        scorings.iter().map(|e| e.name()).collect(),
+    )
+    .raw_prompt()?;
+
+    let rules = select_rules(Tournament::table_sizes(roster.len())[0])?;
+
+    let mut tournament = Tournament::new(roster, scorings[selected.index])?;
+    tournament.rules = rules;
+    for session in 1..=session_count {
+        let seating = tournament.next_seating(&mut rand::thread_rng());
+        println!("{}", i18n::text_with("tournament.session", &[&session]));
+        print!("{}", tournament.pairings_text(&seating));
+
+        for record in tournament.play_session(seating)? {
+            store.record_game(&record);
+        }
+        save_store(store, store_path);
+
+        println!("{}", i18n::text_with("tournament.standings", &[&session]));
+        print!("{}", tournament.standings_text());
+    }
+    Ok(())
+}
+
+/// Plays the same deal set at several tables. The deal set is loaded from the given file or generated from a seed and saved.
+fn play_duplicate(
+    store: &mut ProfileStore,
+    store_path: &Option<PathBuf>,
+    deals_path: Option<String>,
+) -> Result<(), WizardError> {
+    println!("{}", i18n::text("duplicate.title"));
+
+    // the deal set has a deck for every round of the rules
+    let (deal_set, rules) = match deals_path {
+        Some(path) => match DealSet::load(Path::new(&path)) {
+            Ok(deal_set) => {
+                let rules = select_rules(deal_set.player_count as usize)?;
+                (deal_set, rules)
+            }
+            Err(e) => {
+                println!("{}", i18n::text_with("duplicate.not_loaded", &[&path, &e]));
+                return Ok(());
+            }
+        },
+        None => {
+            let player_count = select_number(&i18n::text("duplicate.ask_players"), 3..=6)?;
+            let rules = select_rules(player_count as usize)?;
+
+            let seed = loop {
+                let input = Text::new(&i18n::text("duplicate.ask_seed")).prompt()?;
+                if input.trim().is_empty() {
+                    break rand::random::<u64>();
+                }
+                match input.trim().parse::<u64>() {
+                    Ok(seed) => break seed,
+                    Err(e) => println!("{}", WizardError::Input(e.to_string())),
+                }
+            };
+
+            let deal_set = DealSet::generate(seed, player_count, &rules.schedule)?;
+            let path = format!("wizard-deals-{}.json", seed);
+            match deal_set.save(Path::new(&path)) {
+                Ok(()) => println!("{}", i18n::text_with("duplicate.saved", &[&path])),
+                Err(e) => println!("{}", i18n::text_with("main.not_saved", &[&path, &e])),
+            }
+            (deal_set, rules)
+        }
+    };
+    println!(
+        "{}",
+        i18n::text_with(
+            "duplicate.deal_set",
+            &[&deal_set.seed, &deal_set.player_count]
+        )
+    );
+
+    let table_count = select_number(&i18n::text("duplicate.ask_tables"), 2..=6)?;
+
+    let mut records = Vec::new();
+    for table in 1..=table_count {
+        println!("{}", i18n::text_with("duplicate.table", &[&table]));
+        let mut game = Game {
+            players: select_players(store, deal_set.player_count..=deal_set.player_count)?,
+            deal_set: Some(deal_set.clone()),
+            rules: rules.clone(),
+            ..Game::default()
+        };
+        game.play()?;
+
+        store.record_game(&game.record);
+        save_store(store, store_path);
+        records.push(game.record);
+    }
+
+    println!("{}", i18n::text("duplicate.seat_comparison"));
+    print!("{}", DuplicateResult::new(&records).to_text());
+    Ok(())
+}
@@ -1,10 +1,37 @@
 #[cfg(test)]
+#[allow(clippy::module_inception, clippy::vec_init_then_push)]
 mod tests {
-    use crate::wizard::game::Game;
+    use crate::wizard::advisor::{BidAdvice, PlayAdvice};
+    use crate::wizard::arena::Arena;
+    use crate::wizard::bot::Difficulty;
+    use crate::wizard::chat::{self, Emote};
+    use crate::wizard::duplicate::{DealSet, DuplicateResult};
+    use crate::wizard::error::WizardError;
+    use crate::wizard::external::{self, ExternalBot, Fault};
+    use crate::wizard::game::{Action, Game, Phase};
+    use crate::wizard::i18n::{self, Language};
+    use crate::wizard::lobby::{self, Lobby, LobbyClient, RoomStatus};
+    use crate::wizard::net::{self, Client, ClientMessage, Server, ServerMessage, TimeoutPolicy};
+    use crate::wizard::profile::{Profile, ProfileStore};
+    use crate::wizard::rating;
+    use crate::wizard::record::{Event, GameRecord};
+    use crate::wizard::report::Report;
+    use crate::wizard::round::{Move, Round};
+    use crate::wizard::rules::{RuleSet, Schedule};
+    use crate::wizard::simulation::Simulation;
+    use crate::wizard::solver::{Goal, Solver};
+    use crate::wizard::tournament::{Scoring, Tournament};
+    use crate::wizard::tutorial::{self, Lesson, Task, LEARNER};
     use crate::wizard::{
-        card::{Card, CardColor},
-        player::Player,
+        card::{Card, CardColor, CardCounter},
+        player::{Player, PlayerId},
     };
+    use proptest::prelude::*;
+    use std::io::{self, BufReader};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn card_value() {
@@ -43,10 +70,11 @@ mod tests {
 
     fn new_player(name: String) -> Player {
         Player {
-            name: name,
-            cards: Vec::new(),
-            guess_stitches: 0,
-            actual_stitches: 0,
+            id: PlayerId::next(),
+            name,
+            advisor: false,
+            bot: None,
+            external: None,
         }
     }
 
@@ -57,27 +85,28 @@ mod tests {
             main_color: &CardColor,
             winner: &Player,
         ) {
-            let eval_winner = Card::evaluate_winner(cards.clone(), Some(main_color.clone()));
+            let trick = cards
+                .iter()
+                .map(|(card, player)| (*card, player.id))
+                .collect();
+            let eval_winner = Card::evaluate_winner(trick, Some(*main_color)).unwrap();
             assert_eq!(
-                winner.clone(),
-                eval_winner.clone(),
-                "Input ({:?}): {:#?}. Correct winner: {}. Eval Winner: {}",
-                main_color,
-                cards,
-                winner.name,
-                eval_winner.name
+                winner.id, eval_winner,
+                "Input ({:?}): {:#?}. Correct winner: {}. Eval Winner: {:?}",
+                main_color, cards, winner.name, eval_winner
             )
         }
 
         fn check_winner_without_color(cards: &Vec<(Card, Player)>, winner: &Player) {
-            let eval_winner = Card::evaluate_winner(cards.clone(), None);
+            let trick = cards
+                .iter()
+                .map(|(card, player)| (*card, player.id))
+                .collect();
+            let eval_winner = Card::evaluate_winner(trick, None).unwrap();
             assert_eq!(
-                winner.clone(),
-                eval_winner.clone(),
-                "Input (None): {:#?}. Correct winner: {}. Eval Winner: {}",
-                cards,
-                winner.name,
-                eval_winner.name
+                winner.id, eval_winner,
+                "Input (None): {:#?}. Correct winner: {}. Eval Winner: {:?}",
+                cards, winner.name, eval_winner
             )
         }
 
@@ -190,15 +219,12 @@ mod tests {
 
     #[test]
     fn get_forced_color() {
-        fn check(cards: &Vec<Card>, correct_color: Option<CardColor>) {
-            let eval_color = Card::get_prio_color(cards.clone());
+        fn check(cards: &[Card], correct_color: Option<CardColor>) {
+            let eval_color = Card::get_prio_color(cards.to_vec());
             assert_eq!(
-                eval_color,
-                correct_color,
+                eval_color, correct_color,
                 "Error with input: {:#?}. Correct is {:?}. Output was {:?}",
-                cards.clone(),
-                correct_color.clone(),
-                eval_color.clone()
+                cards, correct_color, eval_color
             );
         }
 
@@ -257,9 +283,2341 @@ mod tests {
         assert_eq!(60, cards.len());
     }
 
+    fn new_counter(hand: Vec<Card>, trick: Vec<Card>) -> CardCounter {
+        CardCounter {
+            hand,
+            played: vec![Card::Number(1, CardColor::GREEN), Card::Wizard],
+            trick,
+            trump_card: Some(Card::Number(5, CardColor::BLUE)),
+            main_color: Some(CardColor::BLUE),
+            player_count: 4,
+        }
+    }
+
+    #[test]
+    fn unseen_cards() {
+        let counter = new_counter(
+            vec![Card::Wizard, Card::Fool],
+            vec![Card::Number(7, CardColor::RED)],
+        );
+        let unseen = counter.unseen_cards();
+
+        assert_eq!(54, unseen.len());
+        assert_eq!(
+            2,
+            unseen.iter().filter(|card| **card == Card::Wizard).count()
+        );
+        assert_eq!(3, unseen.iter().filter(|card| **card == Card::Fool).count());
+        assert!(!unseen.contains(&Card::Number(5, CardColor::BLUE)));
+        assert!(!unseen.contains(&Card::Number(7, CardColor::RED)));
+    }
+
+    #[test]
+    fn win_probability() {
+        let red_7 = Card::Number(7, CardColor::RED);
+        let red_13 = Card::Number(13, CardColor::RED);
+        let blue_13 = Card::Number(13, CardColor::BLUE);
+
+        // a Wizard always wins if no other Wizard was played before
+        let counter = new_counter(vec![Card::Wizard], vec![red_7]);
+        assert_eq!(1.0, counter.win_probability(Card::Wizard));
+        assert_eq!(
+            0.0,
+            new_counter(vec![], vec![Card::Wizard]).win_probability(blue_13)
+        );
+
+        // a Fool never wins a trick with a number in it
+        assert_eq!(0.0, counter.win_probability(Card::Fool));
+
+        // the last player knows the outcome
+        let counter = new_counter(
+            vec![red_13],
+            vec![red_7, Card::Fool, Card::Number(2, CardColor::RED)],
+        );
+        assert_eq!(1.0, counter.win_probability(red_13));
+
+        // only the three unseen Wizards beat the highest trump
+        let counter = new_counter(vec![blue_13], vec![]);
+        let unseen = counter.unseen_cards().len() as f64;
+        let expected = (unseen - 3.0) / unseen * (unseen - 4.0) / (unseen - 1.0) * (unseen - 5.0)
+            / (unseen - 2.0);
+        assert!((counter.win_probability(blue_13) - expected).abs() < 1e-9);
+
+        let probability = new_counter(vec![red_7], vec![]).win_probability(red_7);
+        assert!(probability > 0.0 && probability < counter.win_probability(blue_13));
+    }
+
+    #[test]
+    fn expected_tricks() {
+        let counter = new_counter(vec![Card::Wizard, Card::Wizard], vec![]);
+        assert_eq!(2.0, counter.expected_tricks());
+
+        let counter = new_counter(vec![Card::Fool, Card::Number(1, CardColor::RED)], vec![]);
+        assert!(counter.expected_tricks() < 0.25);
+
+        let strong = new_counter(
+            vec![Card::Wizard, Card::Number(13, CardColor::BLUE)],
+            vec![],
+        );
+        let weak = new_counter(
+            vec![Card::Wizard, Card::Number(2, CardColor::YELLOW)],
+            vec![],
+        );
+        assert!(strong.expected_tricks() > weak.expected_tricks());
+    }
+
     #[test]
     fn stitch_options() {
         let case_1 = Game::stitch_options(5, 3, true);
-        assert_eq!(vec![1, 3, 4, 5], case_1)
+        assert_eq!(vec![0, 1, 3, 4, 5], case_1);
+
+        let case_2 = Game::stitch_options(3, 3, false);
+        assert_eq!(vec![0, 1, 2, 3], case_2);
+
+        // the sum of all guesses already exceeds the amount of cards
+        let case_3 = Game::stitch_options(1, 2, true);
+        assert_eq!(vec![0, 1], case_3);
+    }
+
+    #[test]
+    fn points() {
+        assert_eq!(20, Game::points(0, 0));
+        assert_eq!(50, Game::points(3, 3));
+        assert_eq!(-10, Game::points(0, 1));
+        assert_eq!(-30, Game::points(4, 1));
+    }
+
+    #[test]
+    fn assign_cards() {
+        let mut game = Game::default();
+        for i in 1..=4 {
+            game.players.push(new_player(format!("Player {}", i)));
+        }
+
+        let (hands, trump_card) = game.assign_cards(1, 5).unwrap();
+        let mut dealt: Vec<Card> = hands.iter().flat_map(|e| e.1.clone()).collect();
+        dealt.extend(trump_card);
+        assert!(hands.iter().all(|e| e.1.len() == 5));
+        let ids: Vec<PlayerId> = hands.iter().map(|e| e.0).collect();
+        assert_eq!(game.players.iter().map(|e| e.id).collect::<Vec<_>>(), ids);
+        for card in Card::all_cards() {
+            let in_deck = Card::all_cards().iter().filter(|e| **e == card).count();
+            assert!(dealt.iter().filter(|e| **e == card).count() <= in_deck);
+        }
+
+        // there is no trump card in the last round
+        assert_eq!(None, game.assign_cards(1, 15).unwrap().1);
+        assert_eq!(
+            Err(WizardError::InvalidDealSize {
+                amount: 16,
+                max: 15
+            }),
+            game.assign_cards(1, 16)
+        );
+        assert_eq!(
+            Err(WizardError::InvalidDealSize { amount: 0, max: 15 }),
+            game.assign_cards(1, 0)
+        );
+    }
+
+    #[test]
+    fn playable_cards() {
+        let hand = vec![
+            Card::Number(3, CardColor::RED),
+            Card::Number(8, CardColor::GREEN),
+            Card::Wizard,
+        ];
+
+        // the color of the trick has to be followed
+        let trick = vec![Card::Fool, Card::Number(11, CardColor::GREEN)];
+        assert_eq!(
+            vec![Card::Number(8, CardColor::GREEN), Card::Wizard],
+            Card::playable_cards(hand.clone(), trick)
+        );
+
+        // anything may be played without the color of the trick, on a Wizard or on an empty trick
+        let trick = vec![Card::Number(2, CardColor::BLUE)];
+        assert_eq!(hand, Card::playable_cards(hand.clone(), trick));
+        let trick = vec![Card::Wizard, Card::Number(2, CardColor::RED)];
+        assert_eq!(hand, Card::playable_cards(hand.clone(), trick));
+        assert_eq!(hand, Card::playable_cards(hand.clone(), Vec::new()));
+    }
+
+    #[test]
+    fn bid_advice() {
+        let counter = new_counter(
+            vec![
+                Card::Wizard,
+                Card::Number(12, CardColor::BLUE),
+                Card::Number(2, CardColor::RED),
+            ],
+            vec![],
+        );
+
+        let advice = BidAdvice::new(&counter, &[0, 1, 2, 3]);
+        assert_eq!(2, advice.bid);
+        assert_eq!("1 Wizard + 1 high trump ≈ 2 tricks", advice.rationale);
+
+        // the closest allowed option is suggested
+        let advice = BidAdvice::new(&counter, &[0, 1, 3]);
+        assert!(advice.bid == 1 || advice.bid == 3);
+        assert_eq!(
+            "1 Wizard + 1 high trump ≈ 2 tricks, but 2 may not be bid",
+            advice.rationale
+        );
+
+        let counter = new_counter(vec![Card::Fool, Card::Number(2, CardColor::RED)], vec![]);
+        let advice = BidAdvice::new(&counter, &[0, 1, 2]);
+        assert_eq!(0, advice.bid);
+        assert_eq!("no Wizards or high cards ≈ 0 tricks", advice.rationale);
+    }
+
+    #[test]
+    fn play_advice() {
+        let hand = vec![
+            Card::Wizard,
+            Card::Number(9, CardColor::RED),
+            Card::Number(4, CardColor::RED),
+        ];
+        let counter = new_counter(hand.clone(), vec![Card::Number(6, CardColor::RED)]);
+        let options = Card::playable_cards(hand, counter.trick.clone());
+
+        // a player who still needs a trick plays the Wizard
+        let advice = PlayAdvice::new(&counter, &options, 1, 0);
+        assert_eq!(Card::Wizard, advice.card);
+        assert!(advice.wins_trick);
+        assert_eq!(1.0, advice.win_probability);
+
+        // a player who already has enough tricks stays below the trick
+        let advice = PlayAdvice::new(&counter, &options, 1, 1);
+        assert_eq!(Card::Number(4, CardColor::RED), advice.card);
+        assert!(!advice.wins_trick);
+        assert_eq!(0.0, advice.win_probability);
+        assert_eq!("Play Red 4: does not win the trick", advice.rationale());
+    }
+
+    fn new_record() -> GameRecord {
+        let (max, david, karl) = (PlayerId(1), PlayerId(2), PlayerId(3));
+        let played = |player: PlayerId, card: Card| Event::Played { player, card };
+        let guessed = |player: PlayerId, stitches: u32| Event::Guessed { player, stitches };
+
+        GameRecord {
+            events: vec![
+                Event::GameStarted {
+                    players: [(max, "Max"), (david, "David"), (karl, "Karl")]
+                        .into_iter()
+                        .map(|(id, name)| Player {
+                            id,
+                            ..new_player(name.to_string())
+                        })
+                        .collect(),
+                },
+                Event::RoundStarted {
+                    round: 2,
+                    hands: vec![
+                        (max, vec![Card::Wizard, Card::Number(2, CardColor::RED)]),
+                        (
+                            david,
+                            vec![
+                                Card::Number(5, CardColor::RED),
+                                Card::Number(9, CardColor::RED),
+                            ],
+                        ),
+                        (karl, vec![Card::Fool, Card::Number(3, CardColor::GREEN)]),
+                    ],
+                    trump_card: Some(Card::Number(7, CardColor::RED)),
+                    main_color: Some(CardColor::RED),
+                },
+                guessed(max, 0),
+                guessed(david, 1),
+                guessed(karl, 0),
+                played(max, Card::Wizard),
+                played(david, Card::Number(9, CardColor::RED)),
+                played(karl, Card::Fool),
+                Event::TrickWon { player: max },
+                played(max, Card::Number(2, CardColor::RED)),
+                played(david, Card::Number(5, CardColor::RED)),
+                played(karl, Card::Number(3, CardColor::GREEN)),
+                Event::TrickWon { player: david },
+                Event::RoundScored {
+                    points: vec![(max, -10), (david, 30), (karl, 20)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn game_record() {
+        let record = new_record();
+        assert_eq!(vec!["Max", "David", "Karl"], record.player_names());
+        assert_eq!("David", record.name(PlayerId(2)));
+        assert_eq!("Player 9", record.name(PlayerId(9)));
+
+        let path = std::env::temp_dir().join("wizard-test-game-record.json");
+        record.save(&path).unwrap();
+        let loaded = GameRecord::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(record, loaded);
+    }
+
+    #[test]
+    fn report() {
+        let report = Report::new(&new_record());
+        assert_eq!(3, report.players.len());
+
+        let max = &report.players[0];
+        assert_eq!(-10, max.score);
+        assert_eq!(1, max.rounds.len());
+        assert_eq!(0, max.rounds[0].guess_stitches);
+        assert_eq!(1, max.rounds[0].actual_stitches);
+        assert_eq!(30, max.rounds[0].points_lost);
+        assert_eq!(1, max.rounds[0].moments.len());
+        assert_eq!(1, max.rounds[0].moments[0].trick);
+        assert_eq!(
+            vec![Card::Number(2, CardColor::RED)],
+            max.rounds[0].moments[0].alternatives
+        );
+        assert!(!max.rounds[0].moments[0].would_win);
+
+        // players who got their guess have nothing to improve
+        let david = &report.players[1];
+        assert_eq!(0, david.rounds[0].points_lost);
+        assert!(david.rounds[0].moments.is_empty());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## Max (-10 points)"));
+        assert!(markdown.contains("| 2 | 0 | 1 | -10 | 30 |"));
+        assert!(markdown
+            .contains("- Round 2, trick 1: Red 2 instead of Wizard would have lost the trick."));
+
+        let json: Report = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(report, json);
+    }
+
+    #[test]
+    fn profile_store() {
+        let mut store = ProfileStore::default();
+        store.record_game(&new_record());
+        store.record_game(&new_record());
+
+        let max = store.profile("Max").unwrap();
+        assert_eq!(2, max.games_played);
+        assert_eq!(0, max.wins);
+        assert_eq!(-10.0, max.average_score());
+        assert_eq!(0.0, max.bids.rate());
+
+        let karl = store.profile("Karl").unwrap();
+        assert_eq!(2, karl.bids.hit);
+        assert_eq!(2, karl.bids_by_round_size[&2].rounds);
+
+        let leaderboard: Vec<&str> = store
+            .leaderboard()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(vec!["David", "Karl", "Max"], leaderboard);
+        assert_eq!((2, 0, 0), store.head_to_head("David", "Max"));
+        assert_eq!((0, 2, 0), store.head_to_head("Max", "Karl"));
+        assert_eq!((0, 0, 0), store.head_to_head("Max", "Nobody"));
+        assert!(store.stats().contains("Karl vs Max: 2-0-0"));
+
+        let path = std::env::temp_dir()
+            .join("wizard-test-profiles")
+            .join("profiles.json");
+        store.save(&path).unwrap();
+        assert_eq!(store, ProfileStore::load(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ProfileStore::default(), ProfileStore::load(&path).unwrap());
+    }
+
+    #[test]
+    fn rating_changes() {
+        assert_eq!(0.5, rating::expected_result(1500.0, 1500.0));
+        assert!(rating::expected_result(1700.0, 1500.0) > 0.75);
+        assert_eq!(1.0, rating::margin_factor(0));
+        assert_eq!(2.0, rating::margin_factor(250));
+
+        // equal players: the winner gains what the loser loses
+        let changes = rating::rating_changes(&[(1500.0, 60), (1500.0, 40)]);
+        assert!((changes[0] - 16.0 * 1.2).abs() < 1e-9);
+        assert!((changes[0] + changes[1]).abs() < 1e-9);
+
+        // a higher score margin counts more
+        let close = rating::rating_changes(&[(1500.0, 41), (1500.0, 40)]);
+        assert!(changes[0] > close[0]);
+
+        // a draw between equal players changes nothing
+        let draw = rating::rating_changes(&[(1500.0, 30), (1500.0, 30), (1500.0, 30)]);
+        assert_eq!(vec![0.0, 0.0, 0.0], draw);
+
+        // the favourite loses rating for a draw
+        let upset = rating::rating_changes(&[(1800.0, 30), (1400.0, 30)]);
+        assert!(upset[0] < 0.0 && upset[1] > 0.0);
+
+        assert_eq!(vec![0.0], rating::rating_changes(&[(1500.0, 10)]));
+    }
+
+    #[test]
+    fn profile_ratings() {
+        let mut store = ProfileStore::default();
+        store.record_game(&new_record());
+
+        let rating = |name: &str| store.profile(name).unwrap().rating;
+        assert!(rating("David") > rating::INITIAL_RATING);
+        assert!(rating("Max") < rating::INITIAL_RATING);
+        assert!(rating("David") > rating("Karl") && rating("Karl") > rating("Max"));
+
+        // profiles without a rating get the initial one
+        let json = r#"{"profiles": [{"name": "Max", "games_played": 0, "wins": 0, "total_score": 0, "bids": {"rounds": 0, "hit": 0}, "bids_by_round_size": {}}], "games": []}"#;
+        let store: ProfileStore = serde_json::from_str(json).unwrap();
+        assert_eq!(rating::INITIAL_RATING, store.profiles[0].rating);
+        assert_eq!(None, store.profiles[0].bot);
+    }
+
+    #[test]
+    fn bot_game() {
+        let mut game = Game::default();
+        game.players.push(Player::bot(Difficulty::Easy, 1));
+        game.players.push(Player::bot(Difficulty::Medium, 1));
+        game.players.push(Player::bot(Difficulty::Hard, 1));
+        game.players.push(Player::bot(Difficulty::Hard, 2));
+
+        for round_number in 1..=game.round_limit() {
+            game.play_round().unwrap();
+
+            let round = game.round.as_ref().unwrap();
+            assert_eq!(round_number, round.stitches.values().sum::<u32>());
+            assert!(round.hands.values().all(|e| e.is_empty()));
+        }
+
+        let report = Report::new(&game.record);
+        assert_eq!(4, report.players.len());
+        for player in &report.players {
+            assert_eq!(15, player.rounds.len());
+            assert_eq!(game.score(player.id), player.score);
+        }
+
+        let mut store = ProfileStore::default();
+        store.record_game(&game.record);
+        // the bots are rated per difficulty, not per seat
+        assert_eq!(3, store.profiles.len());
+        assert_eq!(None, store.profile("Hard Bot 2"));
+        let hard = store.player_profile(&game.players[3]).unwrap();
+        assert_eq!(
+            ("Hard Bot", Some(Difficulty::Hard)),
+            (hard.name.as_str(), hard.bot)
+        );
+        assert_eq!(2, hard.games_played);
+        let total: f64 = store.profiles.iter().map(|e| e.rating).sum();
+        assert!((total - 3.0 * rating::INITIAL_RATING).abs() < 1e-9);
+
+        let mut bots = game.players.clone();
+        bots[3].name = "Hard Bot 5".to_string();
+        assert_eq!(Some(hard), store.player_profile(&bots[3]));
+        bots[3].bot = None;
+        assert_eq!(None, store.player_profile(&bots[3]));
+    }
+
+    #[test]
+    fn bot_decisions() {
+        let hand = vec![
+            Card::Wizard,
+            Card::Number(9, CardColor::RED),
+            Card::Number(4, CardColor::RED),
+            Card::Number(2, CardColor::GREEN),
+        ];
+        let counter = new_counter(hand.clone(), vec![Card::Number(6, CardColor::RED)]);
+        let options = Card::playable_cards(hand.clone(), counter.trick.clone());
+
+        for difficulty in Difficulty::all_difficulties() {
+            assert!(options.contains(&difficulty.play_card(&counter, &options, 2, 0)));
+            assert!([0, 2].contains(&difficulty.guess_stitches(&counter, &[0, 2])));
+        }
+
+        assert_eq!(
+            Card::Wizard,
+            Difficulty::Medium.play_card(&counter, &options, 1, 0)
+        );
+        assert_eq!(
+            Card::Number(4, CardColor::RED),
+            Difficulty::Medium.play_card(&counter, &options, 0, 0)
+        );
+        assert_eq!(CardColor::RED, Difficulty::Hard.choose_color(&hand));
+    }
+
+    #[test]
+    fn table_sizes() {
+        assert_eq!(vec![3], Tournament::table_sizes(3));
+        assert_eq!(vec![6], Tournament::table_sizes(6));
+        assert_eq!(vec![4, 3], Tournament::table_sizes(7));
+        assert_eq!(vec![5, 4, 4], Tournament::table_sizes(13));
+
+        for player_count in 3..=60 {
+            let sizes = Tournament::table_sizes(player_count);
+            assert_eq!(player_count, sizes.iter().sum::<usize>());
+            assert!(sizes.iter().all(|e| (3..=6).contains(e)));
+        }
+    }
+
+    #[test]
+    fn tournament_scoring() {
+        let points = Scoring::NormalizedScore.points(&[60, 30, 0]);
+        assert_eq!(vec![30.0, 0.0, -30.0], points);
+
+        let points = Scoring::Placement.points(&[60, 30, 30, 0]);
+        assert_eq!(vec![1.0, 0.5, 0.5, 0.0], points);
+    }
+
+    #[test]
+    fn tournament_seating() {
+        let roster = (1..=8).map(|e| Player::bot(Difficulty::Easy, e)).collect();
+        let mut tournament = Tournament::new(roster, Scoring::NormalizedScore).unwrap();
+        tournament
+            .sessions
+            .push(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+
+        // at best every table takes two players of each table of the first session
+        let seating = tournament.next_seating(&mut rand::thread_rng());
+        let repeats: u32 = seating
+            .iter()
+            .map(|table| {
+                let mut repeats = 0;
+                for (i, a) in table.iter().enumerate() {
+                    for b in &table[i + 1..] {
+                        repeats += tournament.meetings(*a, *b);
+                    }
+                }
+                repeats
+            })
+            .sum();
+        assert_eq!(4, repeats);
+    }
+
+    #[test]
+    fn tournament() {
+        let roster = (1..=7)
+            .map(|e| Player::bot(Difficulty::Medium, e))
+            .collect();
+        let mut tournament = Tournament::new(roster, Scoring::NormalizedScore).unwrap();
+
+        for _ in 0..2 {
+            let seating = tournament.next_seating(&mut rand::thread_rng());
+            let records = tournament.play_session(seating).unwrap();
+            assert_eq!(2, records.len());
+        }
+
+        let standings = tournament.standings();
+        assert_eq!(7, standings.len());
+        assert!(standings.iter().all(|e| e.games == 2));
+        assert!(standings.windows(2).all(|e| e[0].points >= e[1].points));
+        let points: f64 = standings.iter().map(|e| e.points).sum();
+        assert!(points.abs() < 1e-9);
+        assert!(tournament.standings_text().contains("Medium Bot 7"));
+    }
+
+    const COLORS: [CardColor; 4] = [
+        CardColor::BLUE,
+        CardColor::GREEN,
+        CardColor::RED,
+        CardColor::YELLOW,
+    ];
+
+    /// Determines the index of the winning card straight from the rules of Wizard.
+    /// This is intentionally written independently of `Card::evaluate_winner` so both can be compared.
+    fn reference_winner(cards: &[Card], main_color: Option<CardColor>) -> usize {
+        if let Some(index) = cards.iter().position(|card| *card == Card::Wizard) {
+            return index;
+        }
+
+        let prio_color = match Card::get_prio_color(cards.to_vec()) {
+            Some(color) => color,
+            // only Fools were played
+            None => return 0,
+        };
+
+        let highest_of = |color: CardColor| {
+            cards
+                .iter()
+                .enumerate()
+                .filter_map(|(index, card)| match card {
+                    Card::Number(value, card_color) if *card_color == color => {
+                        Some((index, *value))
+                    }
+                    _ => None,
+                })
+                .max_by_key(|(_, value)| *value)
+                .map(|(index, _)| index)
+        };
+
+        main_color
+            .and_then(highest_of)
+            .or_else(|| highest_of(prio_color))
+            .unwrap()
+    }
+
+    /// Pairs every card with a distinct player, evaluates the trick and returns the index of the winning player.
+    fn winner_index(cards: &[Card], players: &[Player], main_color: Option<CardColor>) -> usize {
+        let trick: Vec<(Card, PlayerId)> = cards
+            .iter()
+            .copied()
+            .zip(players.iter().map(|e| e.id))
+            .collect();
+        let winner = Card::evaluate_winner(trick, main_color).unwrap();
+
+        players
+            .iter()
+            .position(|player| player.id == winner)
+            .expect("the winner has to be one of the players in the trick")
+    }
+
+    fn numbered_players(count: usize) -> Vec<Player> {
+        (1..=count)
+            .map(|i| new_player(format!("Player {}", i)))
+            .collect()
+    }
+
+    /// Random tricks of 3 to 6 distinct cards taken from a single deck.
+    fn trick_strategy() -> impl Strategy<Value = Vec<Card>> {
+        (3..=6usize).prop_flat_map(|player_count| {
+            Just(Card::all_cards())
+                .prop_shuffle()
+                .prop_map(move |deck| deck[..player_count].to_vec())
+        })
+    }
+
+    fn main_color_strategy() -> impl Strategy<Value = Option<CardColor>> {
+        proptest::option::of(proptest::sample::select(COLORS.to_vec()))
+    }
+
+    proptest! {
+        #[test]
+        fn evaluate_winner_matches_rules(cards in trick_strategy(), main_color in main_color_strategy()) {
+            let players = numbered_players(cards.len());
+            let winner = winner_index(&cards, &players, main_color);
+
+            prop_assert!(winner < cards.len());
+            prop_assert_eq!(winner, reference_winner(&cards, main_color));
+        }
+
+        #[test]
+        fn first_wizard_wins(cards in trick_strategy(), main_color in main_color_strategy()) {
+            let players = numbered_players(cards.len());
+            let winner = winner_index(&cards, &players, main_color);
+
+            if let Some(first_wizard) = cards.iter().position(|card| *card == Card::Wizard) {
+                prop_assert_eq!(winner, first_wizard);
+            } else {
+                prop_assert_ne!(cards[winner], Card::Wizard);
+            }
+        }
+
+        #[test]
+        fn first_fool_wins_all_fools(player_count in 3..=4usize, main_color in main_color_strategy()) {
+            let cards = vec![Card::Fool; player_count];
+            let players = numbered_players(player_count);
+
+            prop_assert_eq!(winner_index(&cards, &players, main_color), 0);
+        }
+
+        #[test]
+        fn trump_beats_other_numbers(cards in trick_strategy(), main_color in proptest::sample::select(COLORS.to_vec())) {
+            prop_assume!(!cards.contains(&Card::Wizard));
+            prop_assume!(cards.iter().any(|card| matches!(card, Card::Number(_, color) if *color == main_color)));
+
+            let players = numbered_players(cards.len());
+            let winner = winner_index(&cards, &players, Some(main_color));
+
+            prop_assert!(matches!(cards[winner], Card::Number(_, color) if color == main_color));
+        }
+
+        #[test]
+        fn winner_independent_of_players(
+            cards in trick_strategy(),
+            main_color in main_color_strategy(),
+            names in Just((1..=6).map(|i| format!("Player {}", i)).collect::<Vec<_>>()).prop_shuffle(),
+            ids in Just((1..=6).map(PlayerId).collect::<Vec<_>>()).prop_shuffle(),
+            bot in proptest::option::of(proptest::sample::select(Difficulty::all_difficulties())),
+        ) {
+            let players = numbered_players(cards.len());
+            let relabeled: Vec<Player> = names
+                .into_iter()
+                .zip(ids)
+                .take(cards.len())
+                .map(|(name, id)| Player {
+                    id,
+                    bot,
+                    ..new_player(name)
+                })
+                .collect();
+
+            prop_assert_eq!(
+                winner_index(&cards, &players, main_color),
+                winner_index(&cards, &relabeled, main_color)
+            );
+        }
+    }
+
+    /// Deals the first cards of the deck to 3 players and lets everybody guess.
+    fn dealt_round(
+        deck: &[Card],
+        players: usize,
+        cards: usize,
+        main_color: Option<CardColor>,
+    ) -> Round {
+        let hands = (0..players)
+            .map(|i| {
+                (
+                    PlayerId(i as u32 + 1),
+                    deck[i * cards..(i + 1) * cards].to_vec(),
+                )
+            })
+            .collect();
+        let dealer = PlayerId(players as u32);
+        let mut round = Round::new(cards as u32, hands, dealer, None, main_color).unwrap();
+        while round.is_bidding() {
+            let stitches = round.stitch_options()[0];
+            round.guess(stitches).unwrap();
+        }
+        round
+    }
+
+    /// Tries every playable card of every player.
+    fn brute_force(round: &mut Round, target: PlayerId, goal: Goal) -> u32 {
+        let Some(player) = round.next_player() else {
+            return round.stitches[&target];
+        };
+        let tricks = round.playable_cards().into_iter().map(|card| {
+            round.play(card).unwrap();
+            let tricks = brute_force(round, target, goal);
+            round.undo();
+            tricks
+        });
+        if (player == target) == (goal == Goal::MostTricks) {
+            tricks.max().unwrap()
+        } else {
+            tricks.min().unwrap()
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn solver_matches_brute_force(
+            deck in Just(Card::all_cards()).prop_shuffle(),
+            main_color in main_color_strategy(),
+            played in 0..3usize,
+        ) {
+            let mut round = dealt_round(&deck, 3, 3, main_color);
+            for _ in 0..played {
+                let card = round.playable_cards()[0];
+                round.play(card).unwrap();
+            }
+
+            let mut solver = Solver::new(&round);
+            for player in round.seats.clone() {
+                for goal in [Goal::MostTricks, Goal::FewestTricks] {
+                    let (tricks, line) = solver.solve(player, goal).unwrap();
+                    prop_assert_eq!(tricks, brute_force(&mut round.clone(), player, goal));
+
+                    // the line is a legal game that ends with exactly these tricks
+                    let mut replayed = round.clone();
+                    for (player, card) in line {
+                        prop_assert_eq!(replayed.next_player(), Some(player));
+                        prop_assert!(replayed.play(card).is_ok());
+                    }
+                    prop_assert!(replayed.is_finished());
+                    prop_assert_eq!(replayed.stitches[&player], tricks);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solver_eight_cards() {
+        let deck = DealSet::generate(7, 3, &Schedule::Full).unwrap().decks[7].clone();
+        let round = dealt_round(&deck, 3, 8, Some(CardColor::GREEN));
+        let solutions = Solver::new(&round).analyse_all();
+
+        assert_eq!(solutions.len(), 3);
+        for solution in solutions {
+            assert!(solution.max_tricks <= 8 && solution.min_tricks <= 8);
+            assert_eq!(solution.max_line.len(), 24);
+            assert_eq!(solution.min_line.len(), 24);
+        }
+    }
+
+    #[test]
+    fn solver_six_players() {
+        // the default limit of `wizard solve` stays fast with the most players, even without optimizations
+        let cards = Solver::max_cards(6) as usize;
+        for seed in 0..3 {
+            let deck = DealSet::generate(seed, 6, &Schedule::Full).unwrap().decks[0].clone();
+            let round = dealt_round(&deck, 6, cards, Some(CardColor::RED));
+            let start = Instant::now();
+            let solutions = Solver::new(&round).analyse_all();
+            assert!(
+                start.elapsed() < Duration::from_secs(20),
+                "{:?}",
+                start.elapsed()
+            );
+
+            assert_eq!(solutions.len(), 6);
+            for solution in solutions {
+                assert_eq!(solution.max_line.len(), 6 * cards);
+                assert_eq!(solution.min_line.len(), 6 * cards);
+            }
+        }
+    }
+
+    #[test]
+    fn tutorial_lessons() {
+        for lesson in Lesson::all_lessons() {
+            assert_eq!(
+                lesson.round.next_player(),
+                Some(LEARNER),
+                "{}",
+                lesson.title
+            );
+            assert_eq!(1, lesson.round.number, "{}", lesson.title);
+            let hand = lesson.round.hands[&LEARNER].clone();
+            match lesson.task {
+                Task::Guess(stitches) => {
+                    assert!(lesson.round.stitch_options().contains(&stitches));
+                    assert!(lesson.try_guess(stitches).passed);
+                    assert!(!lesson.try_guess(stitches + 1).passed);
+                }
+                _ => {
+                    // every lesson can be solved, but not with every card
+                    let passed: Vec<bool> =
+                        hand.iter().map(|e| lesson.try_card(*e).passed).collect();
+                    assert!(passed.contains(&true), "{}", lesson.title);
+                    assert!(passed.contains(&false), "{}", lesson.title);
+                }
+            }
+        }
+
+        let following = &Lesson::all_lessons()[0];
+        assert!(following
+            .try_card(Card::Number(9, CardColor::GREEN))
+            .text
+            .starts_with("You have to follow Blue"));
+        let wizards = &Lesson::all_lessons()[2];
+        assert_eq!(
+            wizards.try_card(Card::Wizard).text,
+            "The first Wizard wins the trick. You won the trick as planned."
+        );
+
+        assert_eq!(
+            tutorial::explain(&[Card::Fool, Card::Fool, Card::Fool], None),
+            "Everybody played a Fool, so the first Fool wins."
+        );
+        assert_eq!(
+            tutorial::explain(
+                &[Card::Number(4, CardColor::GREEN), Card::Fool, Card::Number(9, CardColor::YELLOW)],
+                Some(CardColor::RED)
+            ),
+            "The Green 4 wins, as it is the highest card of Green, the color of the trick, and nobody played \
+             a Wizard or a trump."
+        );
+    }
+
+    #[test]
+    fn catalogs_complete() {
+        let placeholders = |text: &str| -> Vec<usize> {
+            let mut out: Vec<usize> = (0..10)
+                .filter(|i| text.contains(&format!("{{{}}}", i)))
+                .collect();
+            out.dedup();
+            out
+        };
+        let english = Language::English.catalog();
+        for language in Language::all_languages() {
+            let catalog = language.catalog();
+            for (key, text) in english {
+                let translation = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} is missing in {:?}", key, language));
+                assert!(
+                    !translation.is_empty(),
+                    "{} is empty in {:?}",
+                    key,
+                    language
+                );
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(translation),
+                    "{} in {:?}",
+                    key,
+                    language
+                );
+            }
+            for key in catalog.keys() {
+                assert!(english.contains_key(key), "{} is unknown in English", key);
+            }
+        }
+    }
+
+    #[test]
+    fn german_text() {
+        assert_eq!(Card::Fool.name_in(Language::German), "Narr");
+        assert_eq!(
+            Card::Number(12, CardColor::YELLOW).name_in(Language::German),
+            "Gelb 12"
+        );
+        let colors: Vec<String> = CardColor::all_colors()
+            .iter()
+            .map(|e| e.name_in(Language::German))
+            .collect();
+        assert_eq!(colors, vec!["Blau", "Grün", "Rot", "Gelb"]);
+        assert_eq!(
+            i18n::text_in(Language::German, "game.round", &[&2, &20, &2]),
+            "Runde 2 von 20 (2 Karten)"
+        );
+        let difficulties: Vec<String> = Difficulty::all_difficulties()
+            .iter()
+            .map(|e| e.name_in(Language::German))
+            .collect();
+        assert_eq!(difficulties, vec!["Leicht", "Mittel", "Schwer"]);
+        assert_eq!(
+            i18n::text_in(Language::German, "player.bot_numbered", &[&"Schwer", &2]),
+            "Schwer-Bot 2"
+        );
+        assert_eq!(
+            i18n::text_in(Language::German, "rules.lunch", &[]),
+            "Mittagspause"
+        );
+        // presets and difficulties are found by their English names in every language
+        assert_eq!(Some(Difficulty::Medium), Difficulty::from_name(" MEDIUM"));
+        assert_eq!("Lunch", RuleSet::preset("lunch").unwrap().name);
+
+        // the default stays English unless a language is chosen
+        assert_eq!(i18n::language(), Language::English);
+        assert_eq!(Language::from_code("de-DE"), Some(Language::German));
+        assert_eq!(Language::from_code(""), None);
+    }
+
+    #[test]
+    fn evaluate_winner_exhaustive() {
+        // Wizards and Fools are indistinguishable, so every kind of card has to be considered only once.
+        let mut kinds = Card::all_cards();
+        kinds.retain(|card| !matches!(card, Card::Wizard | Card::Fool));
+        kinds.push(Card::Wizard);
+        kinds.push(Card::Fool);
+
+        let players = numbered_players(3);
+        let main_colors = std::iter::once(None).chain(COLORS.iter().copied().map(Some));
+
+        for main_color in main_colors {
+            for first in &kinds {
+                for second in &kinds {
+                    for third in &kinds {
+                        let cards = [*first, *second, *third];
+                        // every number card exists only once in a deck
+                        let is_duplicate =
+                            |a: &Card, b: &Card| a == b && matches!(a, Card::Number(_, _));
+                        if is_duplicate(first, second)
+                            || is_duplicate(first, third)
+                            || is_duplicate(second, third)
+                        {
+                            continue;
+                        }
+
+                        assert_eq!(
+                            winner_index(&cards, &players, main_color),
+                            reference_winner(&cards, main_color),
+                            "Input ({:?}): {:?}",
+                            main_color,
+                            cards
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deal_set() {
+        let deals = DealSet::generate(7, 3, &Schedule::Full).unwrap();
+        assert_eq!(20, deals.decks.len());
+        assert_eq!(deals, DealSet::generate(7, 3, &Schedule::Full).unwrap());
+        assert_ne!(deals, DealSet::generate(8, 3, &Schedule::Full).unwrap());
+        for deck in &deals.decks {
+            assert_eq!(60, deck.len());
+            assert!(Card::all_cards().iter().all(|e| deck.contains(e)));
+        }
+
+        let mut game = Game::default();
+        for i in 1..=3 {
+            game.players.push(new_player(format!("Player {}", i)));
+        }
+        game.deal_set = Some(deals.clone());
+        let (hands, trump_card) = game.assign_cards(2, 2).unwrap();
+        let deck = deals.deck(2).unwrap();
+        assert_eq!(deck[..2], hands[0].1);
+        assert_eq!(deck[4..6], hands[2].1);
+        assert_eq!(Some(deck[6]), trump_card);
+        assert!(deals.deck(21).is_err());
+        assert_eq!(
+            Err(WizardError::InvalidPlayerCount(7)),
+            DealSet::generate(1, 7, &Schedule::Full)
+        );
+
+        // rounds of the same size are dealt from different decks
+        let deals = DealSet::generate(7, 3, &Schedule::UpAndDown(3)).unwrap();
+        assert_eq!(5, deals.decks.len());
+        assert_ne!(deals.deck(1), deals.deck(5));
+        assert_ne!(deals.deck(2), deals.deck(4));
+        let mut game = Game {
+            deal_set: Some(deals.clone()),
+            ..Game::default()
+        };
+        for i in 1..=3 {
+            game.players.push(new_player(format!("Player {}", i)));
+        }
+        assert_ne!(game.assign_cards(1, 1), game.assign_cards(5, 1));
+        assert_eq!(
+            Err(WizardError::InvalidDealSet {
+                player_count: 3,
+                round_count: 5
+            }),
+            game.assign_cards(6, 1)
+        );
+        // the deal set has too few decks for the rounds of the rules
+        assert_eq!(
+            Err(WizardError::InvalidDealSet {
+                player_count: 3,
+                round_count: 5
+            }),
+            game.apply(Action::Start)
+        );
+        game.rules.schedule = Schedule::UpAndDown(3);
+        game.players.push(new_player("Player 4".to_string()));
+        assert_eq!(
+            Err(WizardError::InvalidDealSet {
+                player_count: 3,
+                round_count: 5
+            }),
+            game.apply(Action::Start)
+        );
+        assert_eq!(Phase::Setup, game.phase);
+
+        let path = std::env::temp_dir().join("wizard-test-deal-set.json");
+        deals.save(&path).unwrap();
+        assert_eq!(deals, DealSet::load(&path).unwrap());
+        let mut short = deals.clone();
+        short.decks[2].pop();
+        short.save(&path).unwrap();
+        let error = DealSet::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+
+    #[test]
+    fn duplicate_game() {
+        let deals = DealSet::generate(3, 4, &Schedule::Full).unwrap();
+
+        let mut records = Vec::new();
+        for table in 1..=2 {
+            let mut game = Game {
+                deal_set: Some(deals.clone()),
+                ..Game::default()
+            };
+            for seat in 1..=4 {
+                let number = 2 * seat + table;
+                game.players.push(Player::bot(Difficulty::Hard, number));
+            }
+            game.play().unwrap();
+            records.push(game.record);
+        }
+
+        // every seat got the same cards at both tables
+        let hands = |record: &GameRecord| -> Vec<Vec<Card>> {
+            record
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::RoundStarted { hands, .. } => Some(hands.iter().map(|e| e.1.clone())),
+                    _ => None,
+                })
+                .flatten()
+                .collect()
+        };
+        assert_eq!(hands(&records[0]), hands(&records[1]));
+
+        let result = DuplicateResult::new(&records);
+        assert_eq!(4, result.seats.len());
+        assert_eq!("Hard Bot 3", result.seats[0][0].0);
+        assert_eq!("Hard Bot 4", result.seats[0][1].0);
+        for seat in result.differences() {
+            assert_eq!(2, seat.len());
+            assert_eq!(0.0, seat[0].1 + seat[1].1);
+        }
+    }
+
+    /// Returns the given hands for the players with the ids 0, 1, 2 and so on.
+    fn seated(hands: Vec<Vec<Card>>) -> Vec<(PlayerId, Vec<Card>)> {
+        hands
+            .into_iter()
+            .enumerate()
+            .map(|(i, hand)| (PlayerId(i as u32), hand))
+            .collect()
+    }
+
+    #[test]
+    fn round_undo() {
+        let hands = vec![
+            vec![
+                Card::Number(3, CardColor::RED),
+                Card::Number(9, CardColor::BLUE),
+            ],
+            vec![Card::Wizard, Card::Number(4, CardColor::RED)],
+            vec![Card::Fool, Card::Number(1, CardColor::BLUE)],
+        ];
+        let mut round = Round::new(
+            2,
+            seated(hands),
+            PlayerId(2),
+            Some(Card::Number(7, CardColor::BLUE)),
+            Some(CardColor::BLUE),
+        )
+        .unwrap();
+
+        // the last player may not guess 0, so that the sum of guesses differs from the amount of tricks
+        round.guess(1).unwrap();
+        round.guess(1).unwrap();
+        assert_eq!(vec![1, 2], round.stitch_options());
+        round.guess(1).unwrap();
+        assert!(!round.is_bidding());
+
+        let mut states = vec![round.clone()];
+        round.play(Card::Number(3, CardColor::RED)).unwrap();
+        states.push(round.clone());
+        // the second player has to follow suit with red or play the Wizard
+        assert_eq!(
+            vec![Card::Wizard, Card::Number(4, CardColor::RED)],
+            round.playable_cards()
+        );
+        round.play(Card::Number(4, CardColor::RED)).unwrap();
+        states.push(round.clone());
+        assert_eq!(Ok(Some(PlayerId(1))), round.play(Card::Fool));
+        assert_eq!(
+            vec![0, 1, 0],
+            round.stitches.values().copied().collect::<Vec<u32>>()
+        );
+        assert_eq!(Some(PlayerId(1)), round.next_player());
+        states.push(round.clone());
+        round.play(Card::Wizard).unwrap();
+        round.play(Card::Number(1, CardColor::BLUE)).unwrap();
+        assert_eq!(
+            Ok(Some(PlayerId(1))),
+            round.play(Card::Number(9, CardColor::BLUE))
+        );
+        assert!(round.is_finished());
+        assert_eq!(None, round.next_player());
+
+        for _ in 0..3 {
+            round.undo();
+        }
+        assert_eq!(states[3], round);
+        assert_eq!(Move::Play(Card::Fool), round.undo().unwrap().action);
+        assert_eq!(states[2], round);
+        round.undo();
+        round.undo();
+        assert_eq!(states[0], round);
+        for _ in 0..3 {
+            assert!(matches!(round.undo().unwrap().action, Move::Guess(1)));
+        }
+        assert_eq!(None, round.undo());
+        assert!(round.guesses.is_empty());
+    }
+
+    #[test]
+    fn game_phases() {
+        let mut game = Game {
+            deal_set: Some(DealSet::generate(11, 3, &Schedule::Full).unwrap()),
+            ..Game::default()
+        };
+        game.players.push(new_player("Max".to_string()));
+        game.players.push(new_player("David".to_string()));
+        assert_eq!(
+            Err(WizardError::InvalidPlayerCount(2)),
+            game.apply(Action::Start)
+        );
+        game.players.push(new_player("Karl".to_string()));
+
+        assert_eq!(
+            Err(WizardError::WrongPhase(Phase::Setup)),
+            game.apply(Action::Guess(0))
+        );
+        game.apply(Action::Start).unwrap();
+        assert_eq!(Phase::Dealing, game.phase);
+
+        while game.phase != Phase::Finished {
+            let events = match game.phase {
+                Phase::Dealing => {
+                    let events = game.apply(Action::Deal).unwrap();
+                    // the dealer moves one seat to the left every round
+                    assert_eq!((game.round_number as usize + 1) % 3, game.dealer);
+                    assert_eq!(
+                        game.players[game.dealer].id,
+                        game.round.as_ref().unwrap().dealer
+                    );
+                    events
+                }
+                Phase::TrumpSelection => game.apply(Action::ChooseColor(CardColor::RED)).unwrap(),
+                Phase::Bidding { next_player } => {
+                    let round = game.round.as_ref().unwrap();
+                    assert_eq!(round.bidding_order()[round.guesses.len()], next_player);
+                    assert_eq!(
+                        game.players[game.next_seat(game.dealer)].id,
+                        round.bidding_order()[0]
+                    );
+                    assert_eq!(
+                        Err(WizardError::BidOutOfRange {
+                            stitches: 100,
+                            options: game.round.as_ref().unwrap().stitch_options()
+                        }),
+                        game.apply(Action::Guess(100))
+                    );
+                    game.apply(Action::Guess(0)).unwrap()
+                }
+                Phase::Playing { next_player, .. } => {
+                    let round = game.round.as_ref().unwrap();
+                    let hand = round.hands[&next_player].clone();
+                    let playable = Card::playable_cards(hand.clone(), round.trick.clone());
+                    let illegal = hand.iter().find(|e| !playable.contains(e));
+                    if let Some(card) = illegal {
+                        assert!(matches!(
+                            game.apply(Action::Play(*card)),
+                            Err(WizardError::IllegalPlay { .. })
+                        ));
+                    }
+                    game.apply(Action::Play(playable[0])).unwrap()
+                }
+                Phase::RoundScored => {
+                    assert_eq!(
+                        Err(WizardError::TakeBackDisabled),
+                        game.apply(Action::TakeBack(game.players[0].id))
+                    );
+                    game.apply(Action::NextRound).unwrap()
+                }
+                phase => panic!("Unexpected phase {:?}", phase),
+            };
+            assert_eq!(
+                events,
+                game.record.events[game.record.events.len() - events.len()..]
+            );
+        }
+
+        assert_eq!(20, game.round_number);
+        assert_eq!(
+            Err(WizardError::WrongPhase(Phase::Finished)),
+            game.apply(Action::Deal)
+        );
+        let report = Report::new(&game.record);
+        for player in &report.players {
+            assert_eq!(game.score(player.id), player.score);
+        }
+    }
+
+    #[test]
+    fn game_take_back() {
+        let mut game = Game {
+            deal_set: Some(DealSet::generate(5, 3, &Schedule::Full).unwrap()),
+            undo: true,
+            ..Game::default()
+        };
+        for name in ["Max", "David", "Karl"] {
+            game.players.push(new_player(name.to_string()));
+        }
+        game.apply(Action::Start).unwrap();
+        game.apply(Action::Deal).unwrap();
+        let ids: Vec<PlayerId> = game.players.iter().map(|e| e.id).collect();
+        assert_eq!(
+            Phase::Bidding {
+                next_player: ids[0]
+            },
+            game.phase
+        );
+        assert_eq!(
+            Err(WizardError::NothingToTakeBack),
+            game.apply(Action::TakeBack(ids[0]))
+        );
+
+        game.apply(Action::Guess(1)).unwrap();
+        // only the player who made the move can take it back
+        assert_eq!(
+            Err(WizardError::NothingToTakeBack),
+            game.apply(Action::TakeBack(ids[1]))
+        );
+        game.apply(Action::TakeBack(ids[0])).unwrap();
+        assert_eq!(
+            Phase::Bidding {
+                next_player: ids[0]
+            },
+            game.phase
+        );
+        for _ in 0..3 {
+            game.apply(Action::Guess(1)).unwrap();
+        }
+
+        let events = game.record.events.clone();
+        let scores = game.scores.clone();
+        for id in &ids {
+            assert_eq!(
+                Phase::Playing {
+                    trick: 0,
+                    next_player: *id
+                },
+                game.phase
+            );
+            let card = game.round.as_ref().unwrap().playable_cards()[0];
+            game.apply(Action::Play(card)).unwrap();
+        }
+        assert_eq!(Phase::RoundScored, game.phase);
+        assert!(game.scores.values().any(|e| *e != 0));
+
+        // taking back the last card also takes back the trick and the scores
+        game.apply(Action::TakeBack(ids[2])).unwrap();
+        assert_eq!(
+            Phase::Playing {
+                trick: 0,
+                next_player: ids[2]
+            },
+            game.phase
+        );
+        game.apply(Action::TakeBack(ids[1])).unwrap();
+        game.apply(Action::TakeBack(ids[0])).unwrap();
+        assert_eq!(events, game.record.events);
+        assert_eq!(scores, game.scores);
+    }
+
+    #[test]
+    fn failed_deal() {
+        let mut game = Game {
+            rules: RuleSet {
+                name: "Custom".to_string(),
+                schedule: Schedule::List(vec![1, 11]),
+            },
+            ..Game::default()
+        };
+        for name in ["Max", "David", "Karl"] {
+            game.players.push(new_player(name.to_string()));
+        }
+        game.apply(Action::Start).unwrap();
+        // the second round asks for more cards than the deck has
+        game.rounds[1] = 21;
+        game.apply(Action::Deal).unwrap();
+        while game.phase != Phase::RoundScored {
+            let action = game.legal_actions()[0];
+            game.apply(action).unwrap();
+        }
+        game.apply(Action::NextRound).unwrap();
+
+        let (round_number, dealer) = (game.round_number, game.dealer);
+        for _ in 0..2 {
+            assert!(matches!(
+                game.apply(Action::Deal),
+                Err(WizardError::InvalidDealSize { amount: 21, .. })
+            ));
+            assert_eq!((round_number, dealer), (game.round_number, game.dealer));
+            assert_eq!(Phase::Dealing, game.phase);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(WizardError::EmptyTrick),
+            Card::evaluate_winner(Vec::new(), None)
+        );
+        assert_eq!(
+            Err(WizardError::EmptyTrick),
+            Card::winning_index(Vec::new(), Some(CardColor::RED))
+        );
+
+        let mut game = Game::default();
+        for name in ["Max", "David", "Karl"] {
+            game.players.push(new_player(name.to_string()));
+        }
+        assert_eq!(
+            Err(WizardError::PlayerNotSeated("Anna".to_string())),
+            game.seat_of("Anna")
+        );
+        assert_eq!(Ok(2), game.seat_of("Karl"));
+        assert_eq!(Ok(&game.players[1]), game.player(game.players[1].id));
+        assert!(game.player(PlayerId(u32::MAX)).is_err());
+
+        let hands = seated(vec![vec![Card::Wizard], vec![Card::Fool]]);
+        let mut round = Round::new(1, hands, PlayerId(1), None, None).unwrap();
+        assert_eq!(
+            Err(WizardError::IllegalPlay {
+                card: Card::Wizard,
+                playable: Vec::new()
+            }),
+            round.play(Card::Wizard)
+        );
+        round.guess(1).unwrap();
+        assert_eq!(
+            Err(WizardError::BidOutOfRange {
+                stitches: 0,
+                options: vec![1]
+            }),
+            round.guess(0)
+        );
+        round.guess(1).unwrap();
+        assert!(matches!(
+            round.play(Card::Fool),
+            Err(WizardError::IllegalPlay { .. })
+        ));
+
+        assert_eq!(
+            WizardError::InputCancelled,
+            WizardError::from(inquire::InquireError::OperationCanceled)
+        );
+        assert_eq!(
+            WizardError::InputCancelled,
+            WizardError::from(inquire::InquireError::OperationInterrupted)
+        );
+    }
+
+    #[test]
+    fn round_seats() {
+        let hands = vec![
+            vec![Card::Number(13, CardColor::GREEN), Card::Fool],
+            vec![
+                Card::Number(2, CardColor::GREEN),
+                Card::Number(5, CardColor::RED),
+            ],
+            vec![
+                Card::Number(8, CardColor::GREEN),
+                Card::Number(9, CardColor::RED),
+            ],
+        ];
+        let ids = |seats: [u32; 3]| seats.map(PlayerId).to_vec();
+        let mut round = Round::new(2, seated(hands), PlayerId(0), None, None).unwrap();
+        assert_eq!(ids([1, 2, 0]), round.bidding_order());
+        assert_eq!(PlayerId(1), round.leader);
+        assert_eq!(
+            Err(WizardError::InvalidPlayerCount(0)),
+            Round::new(1, Vec::new(), PlayerId(0), None, None)
+        );
+
+        for stitches in [0, 1, 0] {
+            assert_eq!(
+                Some(round.bidding_order()[round.guesses.len()]),
+                round.next_player()
+            );
+            round.guess(stitches).unwrap();
+        }
+        assert_eq!(
+            vec![0, 0, 1],
+            round.guesses.values().copied().collect::<Vec<u32>>()
+        );
+
+        // the player left of the dealer leads the first trick
+        assert_eq!(ids([1, 2, 0]), round.playing_order());
+        round.play(Card::Number(2, CardColor::GREEN)).unwrap();
+        round.play(Card::Number(8, CardColor::GREEN)).unwrap();
+        assert_eq!(
+            Ok(Some(PlayerId(0))),
+            round.play(Card::Number(13, CardColor::GREEN))
+        );
+
+        // the winner leads the next trick
+        assert_eq!(ids([0, 1, 2]), round.playing_order());
+        assert_eq!(Some(PlayerId(0)), round.next_player());
+        round.play(Card::Fool).unwrap();
+        round.play(Card::Number(5, CardColor::RED)).unwrap();
+        assert_eq!(
+            Ok(Some(PlayerId(2))),
+            round.play(Card::Number(9, CardColor::RED))
+        );
+        assert_eq!(
+            vec![1, 0, 1],
+            round.stitches.values().copied().collect::<Vec<u32>>()
+        );
+        assert_eq!(
+            vec![-10, 20, 30],
+            ids([0, 1, 2])
+                .into_iter()
+                .map(|e| round.points(e))
+                .collect::<Vec<i32>>()
+        );
+    }
+
+    #[test]
+    fn schedules() {
+        let mut rng = rand::thread_rng();
+        for input in ["full", "odd", "updown:4", "random:6", "1,3,5,7,10"] {
+            let schedule: Schedule = input.parse().unwrap();
+            assert_eq!(input, schedule.to_string());
+        }
+        assert_eq!(Ok(Schedule::List(vec![2, 4])), " 2, 4 ".parse::<Schedule>());
+        for input in ["", "odd:2", "updown:x", "1,,3"] {
+            assert_eq!(
+                Err(WizardError::InvalidSchedule(input.to_string())),
+                input.parse::<Schedule>()
+            );
+        }
+
+        assert_eq!(20, Schedule::Full.rounds(3, &mut rng).unwrap().len());
+        assert_eq!(
+            vec![1, 2, 1],
+            Schedule::UpAndDown(2).rounds(5, &mut rng).unwrap()
+        );
+        let random = Schedule::Random(8).rounds(4, &mut rng).unwrap();
+        assert_eq!(8, random.len());
+        assert!(random.iter().all(|e| (1..=15).contains(e)));
+        assert_eq!(
+            Err(WizardError::InvalidDealSize { amount: 0, max: 12 }),
+            Schedule::List(vec![3, 0]).rounds(5, &mut rng)
+        );
+        assert_eq!(
+            Err(WizardError::InvalidSchedule("random:0".to_string())),
+            Schedule::Random(0).rounds(5, &mut rng)
+        );
+        assert_eq!(
+            Some(Schedule::Random(5)),
+            RuleSet::preset("LUNCH").map(|e| e.schedule)
+        );
+    }
+
+    #[test]
+    fn scheduled_game() {
+        let mut game = Game {
+            rules: RuleSet {
+                name: "Custom".to_string(),
+                schedule: Schedule::List(vec![1, 3, 5, 7, 10]),
+            },
+            ..Game::default()
+        };
+        for number in 1..=4 {
+            game.players.push(Player::bot(Difficulty::Medium, number));
+        }
+
+        for cards in [1, 3, 5, 7, 10] {
+            game.play_round().unwrap();
+            let round = game.round.as_ref().unwrap();
+            assert_eq!(cards, round.cards);
+            assert_eq!(cards, round.stitches.values().sum::<u32>());
+        }
+        game.play().unwrap();
+        assert_eq!(5, game.round_number);
+        assert_eq!(Phase::Finished, game.phase);
+
+        let report = Report::new(&game.record);
+        let rounds: Vec<(u32, u32)> = report.players[0]
+            .rounds
+            .iter()
+            .map(|e| (e.round, e.cards))
+            .collect();
+        assert_eq!(vec![(1, 1), (2, 3), (3, 5), (4, 7), (5, 10)], rounds);
+
+        let mut store = ProfileStore::default();
+        store.record_game(&game.record);
+        let sizes: Vec<u32> = store.profiles[0]
+            .bids_by_round_size
+            .keys()
+            .copied()
+            .collect();
+        assert_eq!(vec![1, 3, 5, 7, 10], sizes);
+
+        // both tables of a duplicate game play the same random rounds
+        let rules = RuleSet::preset("lunch").unwrap();
+        let rounds: Vec<Vec<u32>> = (0..2)
+            .map(|_| {
+                let mut game = Game {
+                    deal_set: Some(DealSet::generate(9, 3, &rules.schedule).unwrap()),
+                    rules: rules.clone(),
+                    ..Game::default()
+                };
+                for number in 1..=3 {
+                    game.players.push(Player::bot(Difficulty::Easy, number));
+                }
+                game.apply(Action::Start).unwrap();
+                game.rounds
+            })
+            .collect();
+        assert_eq!(5, rounds[0].len());
+        assert_eq!(rounds[0], rounds[1]);
+    }
+
+    #[test]
+    fn player_list() {
+        let players = Player::parse_list("Ann, bot:hard,Bob,bot:HARD,bot:easy").unwrap();
+        let names: Vec<&str> = players.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            vec!["Ann", "Hard Bot", "Bob", "Hard Bot 2", "Easy Bot"],
+            names
+        );
+        assert_eq!(None, players[0].bot);
+        assert_eq!(Some(Difficulty::Hard), players[3].bot);
+        assert_eq!(
+            Err(WizardError::InvalidPlayer("bot:expert".to_string())),
+            Player::parse_list("Ann,bot:expert")
+        );
+        assert!(Player::parse_list("Ann,,Bob").is_err());
+
+        // players with the same name are told apart
+        let players = Player::parse_list("Ann,Ann,Hard Bot,bot:hard").unwrap();
+        let names: Vec<&str> = players.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(vec!["Ann", "Ann 2", "Hard Bot", "Hard Bot 2"], names);
+    }
+
+    #[test]
+    fn simulation() {
+        let simulation = Simulation {
+            players: Player::parse_list("bot:easy,bot:medium,bot:hard").unwrap(),
+            rules: RuleSet::preset("short").unwrap(),
+            seed: 11,
+            games: 5,
+        };
+        let records = simulation.run().unwrap();
+        let results = simulation.results(&records);
+        assert_eq!(3, results.len());
+        assert!(results.iter().all(|e| e.games == 5));
+        assert!((results.iter().map(|e| e.wins).sum::<f64>() - 5.0).abs() < 1e-9);
+        assert!(results[0].average_score() >= results[2].average_score());
+
+        // the same seed deals the same cards
+        let deals = |records: &[GameRecord]| -> Vec<Event> {
+            records
+                .iter()
+                .flat_map(|e| e.events.iter())
+                .filter(|e| matches!(e, Event::RoundStarted { round: 1, .. }))
+                .cloned()
+                .collect()
+        };
+        assert_eq!(deals(&records), deals(&simulation.run().unwrap()));
+        let total: i64 = records
+            .iter()
+            .map(|e| e.scores().values().map(|e| *e as i64).sum::<i64>())
+            .sum();
+        assert_eq!(total, results.iter().map(|e| e.total_score).sum::<i64>());
+    }
+
+    #[test]
+    fn network_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            let mut game = Game {
+                rules: RuleSet {
+                    name: "Quick".to_string(),
+                    schedule: Schedule::List(vec![1, 2, 3]),
+                },
+                ..Game::default()
+            };
+            for number in 1..=3 {
+                game.players.push(Player::bot(Difficulty::Medium, number));
+            }
+            let mut server = Server::host(&listener, game, &[1]).unwrap();
+            server.play().unwrap();
+            server.game.record
+        });
+
+        let mut client = Client::join(address, "Remote").unwrap();
+        let mut rejected = 0;
+        let mut illegal_sent = false;
+        let mut hands_seen = Vec::new();
+        let scores = client
+            .play(
+                |view| {
+                    assert_eq!(view.viewer, view.next_player);
+                    assert!(!view.options.is_empty());
+                    if !illegal_sent {
+                        illegal_sent = true;
+                        return Ok(Action::Guess(99));
+                    }
+                    Ok(view.options[0])
+                },
+                |client, message| match message {
+                    ServerMessage::Rejected { .. } => rejected += 1,
+                    ServerMessage::Events { events } => {
+                        for event in events {
+                            if let Event::RoundStarted { hands, .. } = event {
+                                for (id, hand) in hands {
+                                    if !hand.is_empty() {
+                                        hands_seen.push(Some(*id) == client.player);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+            )
+            .unwrap();
+
+        let record = host.join().unwrap();
+        assert_eq!(1, rejected);
+        assert_eq!(vec![true; 3], hands_seen);
+        assert_eq!(4, scores.len());
+        assert_eq!("Remote", record.players()[1].name);
+        assert_eq!(record.name(client.player.unwrap()), "Remote");
+        let totals = record.scores();
+        for (name, score) in scores {
+            let id = record.players().iter().find(|e| e.name == name).unwrap().id;
+            assert_eq!(totals[&id], score);
+        }
+    }
+
+    #[test]
+    fn spectators() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            let mut game = Game {
+                rules: RuleSet {
+                    name: "Quick".to_string(),
+                    schedule: Schedule::List(vec![1, 2, 3]),
+                },
+                ..Game::default()
+            };
+            for number in 1..=2 {
+                game.players.push(Player::bot(Difficulty::Hard, number));
+            }
+            let mut server = Server::host(&listener, game, &[0]).unwrap();
+            server.reveal_hands = Some(1);
+            server.admit(move || net::accept(&listener));
+            server.play().unwrap();
+            server.game.record
+        });
+
+        // the spectator comes before the player and doesn't take the seat
+        let mut spectator = Client::watch(address, "Fan").unwrap();
+        let mut player = Client::join(address, "Player").unwrap();
+        spectator
+            .connection
+            .send(&ClientMessage::Act {
+                action: Action::Guess(0),
+            })
+            .unwrap();
+        let watching = thread::spawn(move || {
+            let mut rejected = Vec::new();
+            let mut scored = 0;
+            let mut revealed = Vec::new();
+            let scores = spectator
+                .play(
+                    |_| -> Result<Action, _> { panic!("a spectator has no turn") },
+                    |_, message| match message {
+                        ServerMessage::Rejected { reason } => rejected.push(reason.clone()),
+                        ServerMessage::State { view } => {
+                            assert!(view.hand.is_empty() && view.options.is_empty())
+                        }
+                        ServerMessage::Events { events } => {
+                            for event in events {
+                                match event {
+                                    Event::RoundStarted { hands, .. } => {
+                                        assert!(hands.iter().all(|(_, hand)| hand.is_empty()))
+                                    }
+                                    Event::RoundScored { .. } => scored += 1,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        ServerMessage::Hands { round, hands } => {
+                            assert!(hands.iter().all(|(_, hand)| hand.len() == *round as usize));
+                            revealed.push((*round, scored));
+                        }
+                        _ => {}
+                    },
+                )
+                .unwrap();
+            (rejected, revealed, scores)
+        });
+
+        let scores = player.play(|view| Ok(view.options[0]), |_, _| {}).unwrap();
+        let record = host.join().unwrap();
+        let (rejected, revealed, spectator_scores) = watching.join().unwrap();
+        assert_eq!(vec!["Spectators can't act.".to_string()], rejected);
+        // every round is shown one round later, the last one when the game is over
+        assert_eq!(vec![(1, 2), (2, 3), (3, 3)], revealed);
+        assert_eq!(scores, spectator_scores);
+        assert_eq!(3, record.players().len());
+
+        // the seat is taken, late clients can only watch
+        assert_eq!(
+            Err(WizardError::Network(
+                "The game is full, you can only watch.".to_string()
+            )),
+            Client::join(address, "Late").map(|e| e.player)
+        );
+    }
+
+    #[test]
+    fn accept_errors() {
+        // a client that gives up while connecting is skipped, a broken listener stops admitting clients
+        for kind in [
+            io::ErrorKind::ConnectionAborted,
+            io::ErrorKind::ConnectionReset,
+        ] {
+            assert!(net::connection_failed(&io::Error::from(kind)));
+        }
+        for kind in [io::ErrorKind::InvalidInput, io::ErrorKind::OutOfMemory] {
+            assert!(!net::connection_failed(&io::Error::from(kind)));
+        }
+    }
+
+    #[test]
+    fn network_take_back() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            let mut game = Game {
+                rules: RuleSet {
+                    name: "Quick".to_string(),
+                    schedule: Schedule::List(vec![1, 2]),
+                },
+                undo: true,
+                ..Game::default()
+            };
+            for number in 1..=2 {
+                game.players.push(Player::bot(Difficulty::Medium, number));
+            }
+            let mut server = Server::host(&listener, game, &[2]).unwrap();
+            server.play().unwrap();
+            server.game.record
+        });
+
+        let mut client = Client::join(address, "Remote").unwrap();
+        let mut attempts = Vec::new();
+        let mut rejected = 0;
+        client
+            .play(
+                |view| {
+                    // the bots moved before, neither their move nor the own one may be taken back
+                    if attempts.len() < 2 {
+                        let player = match attempts.len() {
+                            0 => view.players[1].id,
+                            _ => view.viewer.unwrap(),
+                        };
+                        attempts.push(player);
+                        return Ok(Action::TakeBack(player));
+                    }
+                    Ok(view.options[0])
+                },
+                |_, message| {
+                    if let ServerMessage::Rejected { .. } = message {
+                        rejected += 1;
+                    }
+                },
+            )
+            .unwrap();
+
+        let record = host.join().unwrap();
+        assert_eq!(2, rejected);
+        let guesses = record
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::Guessed { .. }))
+            .count();
+        assert_eq!(6, guesses);
+    }
+
+    /// Hosts a game of two bots and one remote player in a thread and returns the address and the record.
+    fn host_remote_game(
+        timeout: Option<Duration>,
+        on_timeout: TimeoutPolicy,
+    ) -> (SocketAddr, thread::JoinHandle<GameRecord>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            let mut game = Game {
+                rules: RuleSet {
+                    name: "Quick".to_string(),
+                    schedule: Schedule::List(vec![1, 2, 3]),
+                },
+                ..Game::default()
+            };
+            for number in 1..=2 {
+                game.players.push(Player::bot(Difficulty::Medium, number));
+            }
+            let mut server = Server::host(&listener, game, &[2]).unwrap();
+            server.timeout = timeout;
+            server.on_timeout = on_timeout;
+            server.admit(move || net::accept(&listener));
+            server.play().unwrap();
+            server.game.record
+        });
+        (address, host)
+    }
+
+    #[test]
+    fn reconnection() {
+        let (address, host) = host_remote_game(None, TimeoutPolicy::AutoPlay);
+        let mut client = Client::join(address, "Sleepy").unwrap();
+        let token = client.token.clone().unwrap();
+
+        // the laptop sleeps on the first turn and the connection is gone
+        let lost = client.play(
+            |_| Err::<Action, _>(WizardError::Network("closed".to_string())),
+            |_, _| {},
+        );
+        assert!(lost.is_err());
+        drop(client);
+
+        assert!(Client::rejoin(address, "wrong token").is_err());
+        let mut client = Client::rejoin(address, &token).unwrap();
+        let mut states = 0;
+        let scores = client
+            .play(
+                |view| Ok(view.options[0]),
+                |_, message| {
+                    if let ServerMessage::State { view } = message {
+                        assert!(!view.hand.is_empty() || view.round_number > 0);
+                        states += 1;
+                    }
+                },
+            )
+            .unwrap();
+
+        let record = host.join().unwrap();
+        assert!(states > 0);
+        assert_eq!(3, scores.len());
+        assert_eq!(Some(record.players()[2].id), client.player);
+        let guesses = record
+            .events
+            .iter()
+            .filter(
+                |e| matches!(e, Event::Guessed { player, .. } if Some(*player) == client.player),
+            )
+            .count();
+        assert_eq!(3, guesses);
+    }
+
+    #[test]
+    fn chat() {
+        let (address, host) = host_remote_game(None, TimeoutPolicy::AutoPlay);
+        let mut client = Client::join(address, "Chatty").unwrap();
+        let mut said = vec![
+            ClientMessage::Chat {
+                text: " Hi all ".to_string(),
+            },
+            ClientMessage::Chat {
+                text: "a".repeat(chat::MAX_LENGTH + 1),
+            },
+        ];
+        // only 5 messages in 10 seconds are passed on
+        said.extend((0..5).map(|_| ClientMessage::Emote {
+            emote: Emote::WellPlayed,
+        }));
+        said.reverse();
+
+        let mut rejected = Vec::new();
+        let mut chat = Vec::new();
+        client
+            .play(
+                |view| Ok(said.pop().unwrap_or(view.options[0].into())),
+                |client, message| match message {
+                    ServerMessage::Rejected { reason } => rejected.push(reason.clone()),
+                    ServerMessage::Events { events } => chat.extend(
+                        events
+                            .iter()
+                            .filter(|e| matches!(e, Event::Chat { .. } | Event::Emote { .. }))
+                            .flat_map(|e| e.describe(&client.players)),
+                    ),
+                    _ => {}
+                },
+            )
+            .unwrap();
+
+        let record = host.join().unwrap();
+        assert_eq!(
+            vec![
+                "Messages can have at most 200 characters.",
+                "You are sending messages too fast."
+            ],
+            rejected
+        );
+        assert_eq!(5, chat.len());
+        assert_eq!("Chatty says: Hi all", chat[0]);
+        assert_eq!("Chatty says: Well played!", chat[1]);
+
+        // replays include the chat
+        let players = record.players();
+        let replayed: Vec<String> = record
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::Chat { .. } | Event::Emote { .. }))
+            .flat_map(|e| e.describe(&players))
+            .collect();
+        assert_eq!(chat, replayed);
+    }
+
+    #[test]
+    fn timeouts() {
+        // a player who never answers gets a legal move chosen every time
+        let (address, host) =
+            host_remote_game(Some(Duration::from_millis(100)), TimeoutPolicy::AutoPlay);
+        let mut connection = Client::join(address, "Idle").unwrap().connection;
+        let mut timed_out = Vec::new();
+        loop {
+            match connection.receive().unwrap() {
+                ServerMessage::TimedOut { action } => timed_out.push(action),
+                ServerMessage::GameOver { .. } => break,
+                _ => {}
+            }
+        }
+        let record = host.join().unwrap();
+        // three guesses and six cards, besides choosing the main color as the dealer
+        let moves = timed_out
+            .iter()
+            .filter(|e| !matches!(e, Action::ChooseColor(_)))
+            .count();
+        assert_eq!(9, moves);
+        assert_eq!(3, record.scores().len());
+
+        // a bot takes over the seat and the connection is closed until the player rejoins
+        let (address, host) = host_remote_game(
+            Some(Duration::from_millis(100)),
+            TimeoutPolicy::Bot(Difficulty::Easy),
+        );
+        let mut client = Client::join(address, "Idle").unwrap();
+        let asleep = client.play(
+            |_| Err::<Action, _>(WizardError::Network("asleep".to_string())),
+            |_, _| {},
+        );
+        assert!(asleep.is_err());
+        assert!(matches!(
+            client.connection.receive(),
+            Ok(ServerMessage::TimedOut { .. })
+        ));
+        // the rest of the game is played by the bot without the player
+        let mut closed = false;
+        while !closed {
+            closed = client.connection.receive::<ServerMessage>().is_err();
+        }
+        let record = host.join().unwrap();
+        assert_eq!(None, record.players()[2].bot);
+        assert_eq!(3, record.scores().len());
+    }
+
+    #[test]
+    fn reference_bot() {
+        // the reference bot runs in a thread and is connected like a process
+        let start = || {
+            let (bot_input, writer) = io::pipe()?;
+            let (reader, bot_output) = io::pipe()?;
+            thread::spawn(move || external::reference_bot(BufReader::new(bot_input), bot_output));
+            Ok(ExternalBot::with_streams(reader, writer))
+        };
+        let conformance = external::check(start, 8).unwrap();
+        assert!(conformance.passed(), "{}", conformance.text());
+        // 3 rounds with 1, 3 and 10 cards
+        assert!(conformance.moves >= 8 * 14);
+        assert_eq!(8, conformance.games);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn external_bot_faults() {
+        let check = |command: &str, timeout: Duration| {
+            let start = || {
+                let mut bot = ExternalBot::spawn(command)?;
+                bot.timeout = timeout;
+                Ok(bot)
+            };
+            external::check(start, 1).unwrap().faults
+        };
+
+        let crashed = check("sh -c exit", external::DEFAULT_TIMEOUT);
+        assert_eq!(1, crashed.len());
+        assert!(matches!(crashed[0], (1, Fault::Crashed { .. })));
+
+        // echoing the messages of the server isn't the protocol, which one is read first depends on the timing
+        let echoed = check("cat", external::DEFAULT_TIMEOUT);
+        assert!(
+            matches!(&echoed[..], [(1, Fault::Crashed { reason })] if reason.contains("no message of the protocol")),
+            "{:?}",
+            echoed
+        );
+
+        let asleep = check("sleep 10", Duration::from_millis(20));
+        assert!(!asleep.is_empty());
+        assert!(asleep.iter().all(|e| e.1 == Fault::TimedOut));
+
+        // a guess of 99 is never an option, the bot gets the turn again until it had enough attempts
+        let script = std::env::temp_dir().join(format!("illegal-bot-{}.sh", std::process::id()));
+        let answer = r#"{"Act":{"action":{"Guess":99}}}"#;
+        std::fs::write(
+            &script,
+            format!("while read line; do echo '{}'; done", answer),
+        )
+        .unwrap();
+        let illegal = check(
+            &format!("sh {}", script.display()),
+            external::DEFAULT_TIMEOUT,
+        );
+        std::fs::remove_file(script).unwrap();
+        assert!(illegal.len() >= 14 * external::MAX_ATTEMPTS);
+        assert_eq!(0, illegal.len() % external::MAX_ATTEMPTS);
+        let action = Action::Guess(99);
+        assert!(illegal.iter().all(|e| e.1 == Fault::IllegalMove { action }));
+
+        assert!(matches!(
+            ExternalBot::spawn("./there-is-no-such-bot"),
+            Err(WizardError::BotProcess(_))
+        ));
+
+        // without a turn there is nothing to play for a bot that crashed
+        let mut bot = ExternalBot::spawn("sh -c exit").unwrap();
+        let mut game = Game::default();
+        assert_eq!(
+            Err(WizardError::WrongPhase(Phase::Setup)),
+            bot.act(&mut game, PlayerId(1))
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn arena() {
+        let mut bots = Player::parse_list("bot:easy,bot:medium,bot:hard").unwrap();
+        bots.push(Player::external("sh -c exit"));
+        let mut arena = Arena::new(bots);
+        arena.rules = RuleSet {
+            name: "Quick".to_string(),
+            schedule: Schedule::List(vec![1, 2]),
+        };
+        arena.table_sizes = vec![3, 4];
+        arena.deals = 2;
+        assert_eq!(5, arena.tables().len());
+        assert_eq!(2 * (4 * 3 + 4), arena.game_count());
+
+        let standings = arena.run().unwrap();
+        for standing in &standings {
+            // 3 tables of 3 and the table of 4, every deal in every rotation
+            assert_eq!(2 * (3 * 3 + 4), standing.games());
+            assert!(standing.margin().is_some());
+            let crashes = if standing.name == "sh" { 26 } else { 0 };
+            assert_eq!(crashes, standing.crashes());
+            assert_eq!(0, standing.timeouts() + standing.illegal_moves());
+        }
+        // the points of a table add up to nothing
+        let total: f64 = standings.iter().flat_map(|e| &e.points).sum();
+        assert!(total.abs() < 1e-6);
+        let wins: f64 = standings.iter().map(|e| e.wins).sum();
+        assert!((wins - arena.game_count() as f64).abs() < 1e-6);
+        assert!(Arena::standings_text(&standings).contains("sh crashed: "));
+
+        arena.table_sizes = vec![5];
+        assert_eq!(Err(WizardError::InvalidPlayerCount(5)), arena.run());
+    }
+
+    #[test]
+    fn lobby() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let bob = Profile {
+            name: "Bob".to_string(),
+            rating: 1612.0,
+            ..Profile::default()
+        };
+        let profiles = ProfileStore {
+            profiles: vec![bob],
+            games: Vec::new(),
+        };
+        let mut lobby = Lobby::new(profiles, None);
+        let rooms = Arc::clone(&lobby.rooms);
+        let store = Arc::clone(&lobby.store);
+        thread::spawn(move || lobby.serve(move || net::accept(&listener)));
+
+        let quick = RuleSet {
+            name: "Quick".to_string(),
+            schedule: Schedule::List(vec![1, 2]),
+        };
+        let mut host = LobbyClient::connect(address).unwrap();
+        host.create("First", quick.clone(), 3).unwrap();
+        let listed = host.create("Second", quick.clone(), 3).unwrap();
+        assert_eq!(vec![1, 2], listed.iter().map(|e| e.id).collect::<Vec<_>>());
+        assert_eq!(RoomStatus::Waiting, listed[1].status);
+        let reason = WizardError::InvalidPlayerCount(7).to_string();
+        assert_eq!(
+            Err(WizardError::Room(reason)),
+            host.create("Large", quick, 7)
+        );
+        assert_eq!(
+            Err(WizardError::Room("The room has 3 free seats.".to_string())),
+            host.start(1)
+        );
+
+        // room 1 has two bots and Ann, room 2 has two players named Bob and a bot that the first one adds
+        host.add_bot(1, Difficulty::Medium).unwrap();
+        host.add_bot(1, Difficulty::Easy).unwrap();
+        let mut ann = LobbyClient::connect(address)
+            .unwrap()
+            .join(1, "Ann")
+            .unwrap();
+        assert!(LobbyClient::connect(address)
+            .unwrap()
+            .join(1, "Dan")
+            .is_err());
+        let mut bob = LobbyClient::connect(address)
+            .unwrap()
+            .join(2, "Bob")
+            .unwrap();
+        let second_bob = LobbyClient::connect(address)
+            .unwrap()
+            .join(2, "Bob")
+            .unwrap();
+        let message = ClientMessage::AddBot {
+            room: 2,
+            difficulty: Difficulty::Hard,
+        };
+        let listed = lobby::request(&mut bob.connection, message).unwrap();
+        assert_eq!(vec!["Bob", "Bob 2", "Hard Bot"], listed[1].players);
+        let initial = rating::INITIAL_RATING;
+        assert_eq!(vec![1612.0, initial, initial], listed[1].ratings);
+
+        lobby::request(&mut ann.connection, ClientMessage::StartGame { room: 1 }).unwrap();
+        lobby::request(&mut bob.connection, ClientMessage::StartGame { room: 2 }).unwrap();
+        let players: Vec<_> = [ann, bob, second_bob]
+            .into_iter()
+            .map(|mut client| {
+                thread::spawn(move || client.play(|view| Ok(view.options[0]), |_, _| {}).unwrap())
+            })
+            .collect();
+        for player in players {
+            assert_eq!(3, player.join().unwrap().len());
+        }
+
+        // the results are recorded after the players got the final scores
+        let finished = || {
+            let rooms = rooms.lock().unwrap();
+            let statuses = rooms.values().map(|e| &e.info.status);
+            statuses
+                .filter(|e| matches!(e, RoomStatus::Finished { .. }))
+                .count()
+        };
+        for _ in 0..100 {
+            if finished() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(2, finished());
+        let store = store.lock().unwrap();
+        let mut names: Vec<_> = store.games.iter().filter_map(|e| e.room.clone()).collect();
+        names.sort();
+        assert_eq!(vec!["First", "Second"], names);
+        assert!(store.profile("Bob 2").is_some());
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn web_game() {
+        use crate::wizard::web;
+        use std::io::{Read, Write};
+        use std::net::TcpStream;
+        use tungstenite::Message;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            let mut game = Game {
+                rules: RuleSet {
+                    name: "Quick".to_string(),
+                    schedule: Schedule::List(vec![2, 1]),
+                },
+                ..Game::default()
+            };
+            for number in 1..=2 {
+                game.players.push(Player::bot(Difficulty::Easy, number));
+            }
+            let mut server = web::host(&listener, game, &[1, 3]).unwrap();
+            server.play().unwrap();
+            server.game.record
+        });
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let page = get("/");
+        assert!(page.starts_with("HTTP/1.1 200 OK"));
+        assert!(page.ends_with(web::PAGE));
+        assert!(get("/favicon.ico").starts_with("HTTP/1.1 404"));
+        // the page takes its texts from the catalog
+        assert!(get("/locale.json").contains("\"web.trick\":\"Trick\""));
+        let keys = web::PAGE.split('"').filter(|e| {
+            e.contains('.')
+                && !e.ends_with('.')
+                && e.chars()
+                    .all(|c| c.is_ascii_lowercase() || c == '.' || c == '_')
+        });
+        for key in keys {
+            assert!(Language::English.catalog().contains_key(key), "{}", key);
+        }
+        // a handshake without the WebSocket headers fails, the seats are still filled afterwards
+        assert!(!get("/ws").starts_with("HTTP/1.1 101"));
+
+        // every tab is a headless client that plays its first option
+        let tabs: Vec<_> = ["Tab A", "Tab B"]
+            .into_iter()
+            .map(|name| {
+                let (mut socket, _) = tungstenite::client(
+                    format!("ws://{}/ws", address),
+                    TcpStream::connect(address).unwrap(),
+                )
+                .unwrap();
+                let join = ClientMessage::Join {
+                    name: name.to_string(),
+                };
+                socket
+                    .send(Message::text(serde_json::to_string(&join).unwrap()))
+                    .unwrap();
+                thread::spawn(move || {
+                    let mut me = None;
+                    let mut turns = 0;
+                    loop {
+                        let text = socket.read().unwrap().into_text().unwrap();
+                        match serde_json::from_str(&text).unwrap() {
+                            ServerMessage::Welcome { player, .. } => me = Some(player),
+                            ServerMessage::State { view } => {
+                                assert_eq!(me, view.viewer);
+                                assert!(view.options.is_empty() || view.next_player == me);
+                            }
+                            ServerMessage::Turn { view } => {
+                                if !matches!(view.options[0], Action::ChooseColor(_)) {
+                                    turns += 1;
+                                }
+                                let action = ClientMessage::Act {
+                                    action: view.options[0],
+                                };
+                                socket
+                                    .send(Message::text(serde_json::to_string(&action).unwrap()))
+                                    .unwrap();
+                            }
+                            ServerMessage::Events { events } => {
+                                for event in events {
+                                    if let Event::RoundStarted { hands, .. } = event {
+                                        assert!(hands
+                                            .iter()
+                                            .all(|(id, hand)| hand.is_empty() || Some(*id) == me));
+                                    }
+                                }
+                            }
+                            ServerMessage::GameOver { scores } => return (turns, scores),
+                            message => panic!("{:?}", message),
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let record = host.join().unwrap();
+        let names: Vec<String> = record.players().into_iter().map(|e| e.name).collect();
+        assert_eq!(vec!["Easy Bot", "Tab A", "Easy Bot 2", "Tab B"], names);
+        for tab in tabs {
+            let (turns, scores) = tab.join().unwrap();
+            // two guesses and three cards, besides choosing the main color as the dealer
+            assert_eq!(5, turns);
+            assert_eq!(4, scores.len());
+        }
     }
 }
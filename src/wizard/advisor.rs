@@ -0,0 +1,161 @@
+use crate::wizard::card::{Card, CardCounter};
+use crate::wizard::i18n;
+
+/// A suggested bid for a player together with a short rationale.
+#[derive(PartialEq, Clone, Debug)]
+pub struct BidAdvice {
+    pub bid: u32,
+    pub rationale: String,
+}
+
+impl BidAdvice {
+    /// Suggests one of the given bid options for the hand in the given `CardCounter`. <br>
+    /// The suggestion is the allowed option closest to the expected amount of tricks of the hand. If the last bidder
+    /// may not bid the rounded amount, the rationale says so.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::advisor::BidAdvice;
+    /// # use wizard::wizard::card::{Card, CardColor, CardCounter};
+    /// let counter = CardCounter {
+    ///     hand: vec![Card::Wizard, Card::Wizard, Card::Number(13, CardColor::RED)],
+    ///     played: Vec::new(),
+    ///     trick: Vec::new(),
+    ///     trump_card: Some(Card::Number(4, CardColor::RED)),
+    ///     main_color: Some(CardColor::RED),
+    ///     player_count: 4,
+    /// };
+    /// let advice = BidAdvice::new(&counter, &[0, 1, 2, 3]);
+    /// assert_eq!(advice.bid, 3);
+    /// assert_eq!(advice.rationale, "2 Wizards + 1 high trump ≈ 3 tricks");
+    /// ```
+    pub fn new(counter: &CardCounter, options: &[u32]) -> Self {
+        let expected = counter.expected_tricks();
+        let rounded = expected.round() as u32;
+
+        let bid = if options.contains(&rounded) {
+            rounded
+        } else {
+            options
+                .iter()
+                .copied()
+                .min_by(|a, b| {
+                    (*a as f64 - expected)
+                        .abs()
+                        .total_cmp(&(*b as f64 - expected).abs())
+                })
+                .unwrap_or(rounded)
+        };
+
+        let wizards = counter
+            .hand
+            .iter()
+            .filter(|card| **card == Card::Wizard)
+            .count();
+        let high_trumps = counter
+            .hand
+            .iter()
+            .filter(|card| match card {
+                Card::Number(value, color) => Some(*color) == counter.main_color && *value >= 10,
+                _ => false,
+            })
+            .count();
+        let high_cards = counter
+            .hand
+            .iter()
+            .filter(|card| match card {
+                Card::Number(value, color) => Some(*color) != counter.main_color && *value >= 12,
+                _ => false,
+            })
+            .count();
+
+        let mut reasons: Vec<String> = Vec::new();
+        for (count, key) in [
+            (wizards, "advisor.wizards"),
+            (high_trumps, "advisor.high_trumps"),
+            (high_cards, "advisor.high_cards"),
+        ] {
+            match count {
+                0 => {}
+                1 => reasons.push(i18n::text(&format!("{}.one", key))),
+                _ => reasons.push(i18n::text_with(&format!("{}.many", key), &[&count])),
+            }
+        }
+        if reasons.is_empty() {
+            reasons.push(i18n::text("advisor.no_high_cards"));
+        }
+
+        let mut rationale = i18n::text_with("advisor.bid", &[&reasons.join(" + "), &rounded]);
+        if bid != rounded {
+            rationale.push_str(&i18n::text_with("advisor.bid_forbidden", &[&rounded]));
+        }
+
+        BidAdvice { bid, rationale }
+    }
+}
+
+/// A suggested card for a player to play next.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PlayAdvice {
+    pub card: Card,
+    /// Whether the card would be on top of the current trick.
+    pub wins_trick: bool,
+    /// The probability that the card still wins the trick after all other players played.
+    pub win_probability: f64,
+}
+
+impl PlayAdvice {
+    /// Suggests one of the given playable cards. <br>
+    /// While the player still needs tricks to reach the bid the card most likely to win is suggested,
+    /// otherwise the card least likely to win. Ties are broken by keeping the strong cards for later.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if no playable cards are given.
+    pub fn new(counter: &CardCounter, options: &[Card], bid: u32, tricks: u32) -> Self {
+        let wants_trick = tricks < bid;
+
+        let (card, win_probability) = options
+            .iter()
+            .map(|card| (*card, counter.win_probability(*card)))
+            .min_by(|(a_card, a_probability), (b_card, b_probability)| {
+                if wants_trick {
+                    b_probability
+                        .total_cmp(a_probability)
+                        .then(a_card.value().cmp(&b_card.value()))
+                } else {
+                    a_probability
+                        .total_cmp(b_probability)
+                        .then(b_card.value().cmp(&a_card.value()))
+                }
+            })
+            .expect("There has to be at least one playable card.");
+
+        let mut trick = counter.trick.clone();
+        trick.push(card);
+        let wins_trick =
+            Card::winning_index(trick.clone(), counter.main_color) == Ok(trick.len() - 1);
+
+        PlayAdvice {
+            card,
+            wins_trick,
+            win_probability,
+        }
+    }
+
+    /// Returns a short explanation of the advice, e.g. `"Play Red 12: takes the trick so far (64% to win it)"`.
+    pub fn rationale(&self) -> String {
+        if self.wins_trick {
+            i18n::text_with(
+                "advisor.play_winning",
+                &[
+                    &self.card.name(),
+                    &format!("{:.0}", self.win_probability * 100.0),
+                ],
+            )
+        } else {
+            i18n::text_with("advisor.play_losing", &[&self.card.name()])
+        }
+    }
+}
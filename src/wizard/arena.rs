@@ -0,0 +1,280 @@
+use crate::wizard::duplicate::DealSet;
+use crate::wizard::error::WizardError;
+use crate::wizard::external::{self, Fault};
+use crate::wizard::game::Game;
+use crate::wizard::i18n;
+use crate::wizard::player::Player;
+use crate::wizard::rules::RuleSet;
+use crate::wizard::tournament::Scoring;
+use std::time::Duration;
+
+/// The results of a bot over all games of an arena.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ArenaStanding {
+    pub name: String,
+    /// The points of every game the bot played, see `Scoring`.
+    pub points: Vec<f64>,
+    /// Games with the highest score. Shared wins count as a fraction.
+    pub wins: f64,
+    /// Everything the bot did wrong, only external bots make mistakes.
+    pub faults: Vec<Fault>,
+}
+
+impl ArenaStanding {
+    pub fn games(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.points.is_empty() {
+            0.0
+        } else {
+            self.points.iter().sum::<f64>() / self.points.len() as f64
+        }
+    }
+
+    /// Returns half the width of the 95% confidence interval of the mean, `None` for less than 2 games.
+    pub fn margin(&self) -> Option<f64> {
+        let count = self.points.len() as f64;
+        if count < 2.0 {
+            return None;
+        }
+        let mean = self.mean();
+        let variance = self.points.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / (count - 1.0);
+        Some(1.96 * (variance / count).sqrt())
+    }
+
+    pub fn crashes(&self) -> usize {
+        self.faults
+            .iter()
+            .filter(|e| matches!(e, Fault::Crashed { .. }))
+            .count()
+    }
+
+    pub fn timeouts(&self) -> usize {
+        self.faults
+            .iter()
+            .filter(|e| **e == Fault::TimedOut)
+            .count()
+    }
+
+    pub fn illegal_moves(&self) -> usize {
+        self.faults
+            .iter()
+            .filter(|e| matches!(e, Fault::IllegalMove { .. }))
+            .count()
+    }
+}
+
+/// A round robin between bots. For every table size, every combination of bots plays every deal once in every seat
+/// rotation, so no bot profits from its seat or from better cards. The bots are ranked by their average points with
+/// a 95% confidence interval, and the crashes, timeouts and illegal moves of external bots are counted.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::arena::Arena;
+/// # use wizard::wizard::player::Player;
+/// # use wizard::wizard::rules::{RuleSet, Schedule};
+/// let mut arena = Arena::new(Player::parse_list("bot:easy,bot:medium,bot:hard,bot:hard")?);
+/// arena.rules = RuleSet { name: "Quick".to_string(), schedule: Schedule::List(vec![1, 2]) };
+/// arena.table_sizes = vec![3];
+/// arena.deals = 2;
+///
+/// // 4 combinations of 3 bots play 2 deals in 3 rotations
+/// assert_eq!(arena.game_count(), 24);
+/// let standings = arena.run()?;
+/// assert_eq!(standings[0].games(), 18);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct Arena {
+    /// The bots that take part, built-in bots and external bots, see `Player::external`.
+    pub bots: Vec<Player>,
+    pub rules: RuleSet,
+    /// The amounts of players at a table.
+    pub table_sizes: Vec<usize>,
+    /// The deals every combination of bots plays. Deal `i` is dealt with the seed `seed + i`.
+    pub deals: u32,
+    pub seed: u64,
+    pub scoring: Scoring,
+    /// How long external bots have for every action.
+    pub timeout: Duration,
+}
+
+impl Arena {
+    /// Returns an arena for the given bots with 3 players at every table and 10 deals.
+    pub fn new(bots: Vec<Player>) -> Self {
+        Arena {
+            bots,
+            rules: RuleSet::default(),
+            table_sizes: vec![3],
+            deals: 10,
+            seed: 0,
+            scoring: Scoring::NormalizedScore,
+            timeout: external::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Returns the tables as indices into `bots`, every combination of bots for every table size.
+    pub fn tables(&self) -> Vec<Vec<usize>> {
+        let mut tables = Vec::new();
+        for size in &self.table_sizes {
+            if *size > self.bots.len() {
+                continue;
+            }
+            let mut table: Vec<usize> = (0..*size).collect();
+            // the combinations in lexicographic order
+            loop {
+                tables.push(table.clone());
+                let Some(index) = (0..*size)
+                    .rev()
+                    .find(|i| table[*i] < self.bots.len() - size + i)
+                else {
+                    break;
+                };
+                table[index] += 1;
+                for i in index + 1..*size {
+                    table[i] = table[i - 1] + 1;
+                }
+            }
+        }
+        tables
+    }
+
+    pub fn game_count(&self) -> usize {
+        let seatings: usize = self.tables().iter().map(|e| e.len()).sum();
+        seatings * self.deals as usize
+    }
+
+    /// Plays all games without printing anything and returns the standings of the bots, the best first. <br>
+    /// `WizardError::InvalidPlayerCount` is returned if a table size is not between 3 and 6 or there are more seats
+    /// than bots, `WizardError::BotProcess` if an external bot can't be started.
+    pub fn run(&self) -> Result<Vec<ArenaStanding>, WizardError> {
+        if let Some(size) = self
+            .table_sizes
+            .iter()
+            .find(|e| !(3..=6).contains(*e) || **e > self.bots.len())
+        {
+            return Err(WizardError::InvalidPlayerCount(*size));
+        }
+
+        let mut standings: Vec<ArenaStanding> = self
+            .bots
+            .iter()
+            .map(|e| ArenaStanding {
+                name: e.name.clone(),
+                points: Vec::new(),
+                wins: 0.0,
+                faults: Vec::new(),
+            })
+            .collect();
+
+        for table in self.tables() {
+            for deal in 0..self.deals {
+                let seed = self.seed.wrapping_add(deal as u64);
+                for rotation in 0..table.len() {
+                    let mut seating = table.clone();
+                    seating.rotate_left(rotation);
+                    self.play(&seating, seed, &mut standings)?;
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| b.mean().total_cmp(&a.mean()));
+        Ok(standings)
+    }
+
+    /// Plays a game with the bots of the seating and adds the results to their standings.
+    fn play(
+        &self,
+        seating: &[usize],
+        seed: u64,
+        standings: &mut [ArenaStanding],
+    ) -> Result<(), WizardError> {
+        let mut game = Game {
+            players: seating.iter().map(|e| self.bots[*e].clone()).collect(),
+            deal_set: Some(DealSet::generate(
+                seed,
+                seating.len() as u32,
+                &self.rules.schedule,
+            )?),
+            rules: self.rules.clone(),
+            ..Game::default()
+        };
+        let mut bots = external::spawn_all(&game)?;
+        for bot in bots.values_mut() {
+            bot.timeout = self.timeout;
+        }
+        external::play(&mut game, &mut bots)?;
+
+        let scores: Vec<i32> = game.players.iter().map(|e| game.score(e.id)).collect();
+        let points = self.scoring.points(&scores);
+        let best = scores.iter().max().copied();
+        let winners = scores.iter().filter(|e| Some(**e) == best).count() as f64;
+        for (seat, bot) in seating.iter().enumerate() {
+            let standing = &mut standings[*bot];
+            standing.points.push(points[seat]);
+            if Some(scores[seat]) == best {
+                standing.wins += 1.0 / winners;
+            }
+            if let Some(external) = bots.get(&game.players[seat].id) {
+                standing.faults.extend(external.faults.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the standings as printable text, followed by the crashes of every bot.
+    pub fn standings_text(standings: &[ArenaStanding]) -> String {
+        let mut out = format!(
+            "{:<4} {:<16} {:>5} {:>8} {:>8} {:>6} {:>7} {:>8} {:>7}\n",
+            i18n::text("arena.rank"),
+            i18n::text("arena.name"),
+            i18n::text("arena.games"),
+            i18n::text("arena.points"),
+            i18n::text("arena.ci"),
+            i18n::text("arena.wins"),
+            i18n::text("arena.crashes"),
+            i18n::text("arena.timeouts"),
+            i18n::text("arena.illegal")
+        );
+        for (rank, standing) in standings.iter().enumerate() {
+            let margin = match standing.margin() {
+                Some(margin) => format!("±{:.2}", margin),
+                None => "-".to_string(),
+            };
+            out.push_str(&format!(
+                "{:<4} {:<16} {:>5} {:>8.2} {:>8} {:>6.1} {:>7} {:>8} {:>7}\n",
+                rank + 1,
+                standing.name,
+                standing.games(),
+                standing.mean(),
+                margin,
+                standing.wins,
+                standing.crashes(),
+                standing.timeouts(),
+                standing.illegal_moves()
+            ));
+        }
+
+        for standing in standings {
+            let mut reasons: Vec<&str> = standing
+                .faults
+                .iter()
+                .filter_map(|e| match e {
+                    Fault::Crashed { reason } => Some(reason.as_str()),
+                    _ => None,
+                })
+                .collect();
+            reasons.dedup();
+            for reason in reasons {
+                out.push_str(&format!(
+                    "{}\n",
+                    i18n::text_with("arena.crashed", &[&standing.name, &reason])
+                ));
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,117 @@
+use crate::wizard::advisor::{BidAdvice, PlayAdvice};
+use crate::wizard::card::{Card, CardColor, CardCounter};
+use crate::wizard::i18n::{self, Language};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+
+/// The strength of a computer controlled player.
+/// - Easy: guesses and plays at random
+/// - Medium: guesses like the advisor and plays its highest or lowest card
+/// - Hard: guesses and plays like the advisor
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the *name* of the given difficulty as a String.
+    pub fn name(&self) -> String {
+        self.name_in(i18n::language())
+    }
+
+    /// Returns the *name* of the given difficulty in the given language.
+    pub fn name_in(&self, language: Language) -> String {
+        let key = match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Medium => "difficulty.medium",
+            Difficulty::Hard => "difficulty.hard",
+        };
+        i18n::text_in(language, key, &[])
+    }
+
+    /// Returns the difficulty with the given name in the current language or in English, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::bot::Difficulty;
+    /// # use wizard::wizard::i18n::Language;
+    /// assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+    /// assert_eq!(Difficulty::Hard.name_in(Language::German), "Schwer");
+    /// assert_eq!(Difficulty::from_name("expert"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::all_difficulties().into_iter().find(|e| {
+            [e.name(), e.name_in(Language::English)]
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(name.trim()))
+        })
+    }
+
+    /// Returns a `Vec<Difficulty>` with all difficulties. (sorted)
+    pub fn all_difficulties() -> Vec<Difficulty> {
+        vec![Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+    }
+
+    /// Selects one of the given options for the amount of stitches.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if no options are given.
+    pub fn guess_stitches(&self, counter: &CardCounter, options: &[u32]) -> u32 {
+        match self {
+            Difficulty::Easy => *options
+                .choose(&mut thread_rng())
+                .expect("There has to be at least one option."),
+            Difficulty::Medium | Difficulty::Hard => BidAdvice::new(counter, options).bid,
+        }
+    }
+
+    /// Selects one of the given playable cards.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if no playable cards are given.
+    pub fn play_card(
+        &self,
+        counter: &CardCounter,
+        options: &[Card],
+        guess_stitches: u32,
+        actual_stitches: u32,
+    ) -> Card {
+        let card = match self {
+            Difficulty::Easy => options.choose(&mut thread_rng()).copied(),
+            Difficulty::Medium if actual_stitches < guess_stitches => {
+                options.iter().copied().max_by_key(|e| e.value())
+            }
+            Difficulty::Medium => options.iter().copied().min_by_key(|e| e.value()),
+            Difficulty::Hard => {
+                Some(PlayAdvice::new(counter, options, guess_stitches, actual_stitches).card)
+            }
+        };
+
+        card.expect("There has to be at least one playable card.")
+    }
+
+    /// Selects the main color of a round, which is the color the bot holds the most cards of.
+    pub fn choose_color(&self, hand: &[Card]) -> CardColor {
+        let colors = CardColor::all_colors();
+        if *self == Difficulty::Easy {
+            return *colors.choose(&mut thread_rng()).unwrap();
+        }
+
+        colors
+            .into_iter()
+            .max_by_key(|color| {
+                hand.iter()
+                    .filter(
+                        |card| matches!(card, Card::Number(_, card_color) if card_color == color),
+                    )
+                    .count()
+            })
+            .unwrap()
+    }
+}
@@ -1,6 +1,9 @@
-use super::player::Player;
+use super::error::WizardError;
+use super::i18n::{self, Language};
+use super::player::PlayerId;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Card {
     Number(u32 /* value */, CardColor /* color */),
     /// value = 14
@@ -64,7 +67,7 @@ impl Card {
         out
     }
 
-    /// Returns the *name* of the given card as a String. <br>
+    /// Returns the *name* of the given card as a String, in the language set with `i18n::set_language`. <br>
     ///
     /// # Examples
     ///
@@ -77,53 +80,96 @@ impl Card {
     /// let card_3: String = Card::Number(1, CardColor::BLUE).name(); // "Blue 1"
     /// ```
     pub fn name(&self) -> String {
+        self.name_in(i18n::language())
+    }
+
+    /// Returns the *name* of the given card in the given language.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::card::{Card, CardColor};
+    /// # use wizard::wizard::i18n::Language;
+    /// assert_eq!(Card::Wizard.name_in(Language::German), "Zauberer");
+    /// assert_eq!(Card::Number(9, CardColor::GREEN).name_in(Language::German), "Grün 9");
+    /// ```
+    pub fn name_in(&self, language: Language) -> String {
         match self {
-            Card::Fool => "Fool".to_string(),
-            Card::Wizard => "Wizard".to_string(),
-            Card::Number(value, color) => match color {
-                CardColor::BLUE => format!("Blue {}", value),
-                CardColor::GREEN => format!("Green {}", value),
-                CardColor::RED => format!("Red {}", value),
-                CardColor::YELLOW => format!("Yellow {}", value),
-            },
+            Card::Fool => i18n::text_in(language, "card.fool", &[]),
+            Card::Wizard => i18n::text_in(language, "card.wizard", &[]),
+            Card::Number(value, color) => {
+                i18n::text_in(language, "card.number", &[&color.name_in(language), value])
+            }
         }
     }
 
-    /// Determines the *winning* `Player` in a round of standard Wizard **with the *main color* considered.**
+    /// Determines the id of the *winning* player in a round of standard Wizard **with the *main color* considered.** <br>
+    /// `WizardError::EmptyTrick` is returned if the given vector is empty as the winner cannot be determined.
     ///
-    /// # Panics
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::card::{Card, CardColor};
+    /// # use wizard::wizard::player::PlayerId;
+    /// let trick = vec![
+    ///     (Card::Number(7, CardColor::BLUE), PlayerId(4)),
+    ///     (Card::Number(2, CardColor::RED), PlayerId(2)),
+    /// ];
+    /// assert_eq!(Card::evaluate_winner(trick.clone(), None), Ok(PlayerId(4)));
+    /// assert_eq!(Card::evaluate_winner(trick, Some(CardColor::RED)), Ok(PlayerId(2)));
+    /// ```
+    pub fn evaluate_winner(
+        cards: Vec<(Card, PlayerId)>,
+        main_color: Option<CardColor>,
+    ) -> Result<PlayerId, WizardError> {
+        let cards_only: Vec<Card> = cards.iter().map(|(card, _)| *card).collect();
+        let index = Card::winning_index(cards_only, main_color)?;
+
+        Ok(cards[index].1)
+    }
+
+    /// Determines the index of the *winning* `Card` in a trick of standard Wizard **with the *main color* considered.** <br>
+    /// The cards have to be in the order they were played. `WizardError::EmptyTrick` is returned if the given vector is empty.
     ///
-    /// This function panics if the given vector is empty as the winner cannot be determined.
-    pub fn evaluate_winner(cards: Vec<(Card, Player)>, main_color: Option<CardColor>) -> Player {
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::card::Card;
+    /// # use wizard::wizard::card::CardColor;
+    /// let trick = vec![
+    ///     Card::Number(7, CardColor::BLUE),
+    ///     Card::Number(12, CardColor::BLUE),
+    ///     Card::Number(2, CardColor::RED),
+    /// ];
+    /// assert_eq!(Card::winning_index(trick.clone(), None), Ok(1));
+    /// assert_eq!(Card::winning_index(trick, Some(CardColor::RED)), Ok(2));
+    /// ```
+    #[allow(clippy::if_same_then_else)]
+    pub fn winning_index(
+        cards: Vec<Card>,
+        main_color: Option<CardColor>,
+    ) -> Result<usize, WizardError> {
         if cards.is_empty() {
-            panic!("Input for this function was empty.");
+            return Err(WizardError::EmptyTrick);
         }
 
-        let mut winner: (Card, Player) = cards[0].clone();
+        let mut winner: (Card, usize) = (cards[0], 0);
         macro_rules! transfer_winner {
-            ($card:expr; $player:expr) => {
+            ($card:expr; $index:expr) => {
                 winner.0 = $card;
-                winner.1 = $player;
+                winner.1 = $index;
             };
         }
-        let prio_color: Option<CardColor>;
-
-        {
-            let mut cards_only: Vec<Card> = Vec::new();
-            for card in cards.clone() {
-                cards_only.push(card.0);
-            }
-            prio_color = Card::get_prio_color(cards_only);
-        }
+        let prio_color: Option<CardColor> = Card::get_prio_color(cards.clone());
 
         match (main_color, prio_color) {
             (Some(main_color), Some(prio_color)) => {
-                for (new_card, new_player) in cards.clone() {
+                for (new_index, new_card) in cards.iter().copied().enumerate() {
                     match (new_card, winner.0) {
-                        (Card::Wizard, _) => return new_player,
+                        (Card::Wizard, _) => return Ok(new_index),
                         (Card::Number(_, _), Card::Fool) => {
                             winner.0 = new_card;
-                            winner.1 = new_player;
+                            winner.1 = new_index;
                         }
                         (
                             Card::Number(new_value, new_color),
@@ -134,22 +180,22 @@ impl Card {
                                 && new_value > old_value
                             {
                                 // same color
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color == main_color && old_color != main_color {
                                 // higher value color
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color == prio_color
                                 && old_color != prio_color
                                 && old_color != main_color
                             {
                                 // higher value color
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color == prio_color
                                 && old_color == prio_color
                                 && new_value > old_value
                             {
                                 // same color
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color != main_color
                                 && new_color != prio_color
                                 && old_color != main_color
@@ -157,7 +203,7 @@ impl Card {
                                 && new_value > old_value
                             {
                                 // same value color
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             }
                         }
                         (_, _) => {}
@@ -165,28 +211,28 @@ impl Card {
                 }
             }
             (None, Some(prio_color)) => {
-                for (new_card, new_player) in cards.clone() {
+                for (new_index, new_card) in cards.iter().copied().enumerate() {
                     match (new_card, winner.0) {
-                        (Card::Wizard, _) => return new_player,
+                        (Card::Wizard, _) => return Ok(new_index),
                         (Card::Number(_, _), Card::Fool) => {
-                            transfer_winner!(new_card; new_player);
+                            transfer_winner!(new_card; new_index);
                         }
                         (
                             Card::Number(new_value, new_color),
                             Card::Number(old_value, old_color),
                         ) => {
                             if new_color == prio_color && old_color != prio_color {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color == prio_color
                                 && old_color == prio_color
                                 && new_value > old_value
                             {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color != prio_color
                                 && old_color != prio_color
                                 && new_value > old_value
                             {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             }
                         }
                         (_, _) => {}
@@ -194,28 +240,28 @@ impl Card {
                 }
             }
             (Some(main_color), None) => {
-                for (new_card, new_player) in cards.clone() {
+                for (new_index, new_card) in cards.iter().copied().enumerate() {
                     match (new_card, winner.0) {
-                        (Card::Wizard, _) => return new_player,
+                        (Card::Wizard, _) => return Ok(new_index),
                         (Card::Number(_, _), Card::Fool) => {
-                            transfer_winner!(new_card; new_player);
+                            transfer_winner!(new_card; new_index);
                         }
                         (
                             Card::Number(new_value, new_color),
                             Card::Number(old_value, old_color),
                         ) => {
                             if new_color == main_color && old_color != main_color {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color == main_color
                                 && old_color == main_color
                                 && new_value > old_value
                             {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             } else if new_color != main_color
                                 && old_color != main_color
                                 && new_value > old_value
                             {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             }
                         }
                         (_, _) => {}
@@ -223,15 +269,15 @@ impl Card {
                 }
             }
             (None, None) => {
-                for (new_card, new_player) in cards.clone() {
+                for (new_index, new_card) in cards.iter().copied().enumerate() {
                     match (new_card, winner.0) {
-                        (Card::Wizard, _) => return new_player,
+                        (Card::Wizard, _) => return Ok(new_index),
                         (Card::Number(_, _), Card::Fool) => {
-                            transfer_winner!(new_card; new_player);
+                            transfer_winner!(new_card; new_index);
                         }
                         (Card::Number(new_value, _), Card::Number(old_value, _)) => {
                             if new_value > old_value {
-                                transfer_winner!(new_card; new_player);
+                                transfer_winner!(new_card; new_index);
                             }
                         }
                         (_, _) => {}
@@ -240,7 +286,7 @@ impl Card {
             }
         }
 
-        winner.1
+        Ok(winner.1)
     }
 
     /// This function takes a `Vec<Card>` as arguments and returns an optional `CardColor` if the color that other players are forced to play can be determined.
@@ -270,6 +316,36 @@ impl Card {
         None
     }
 
+    /// Returns the cards of the given hand that may be played on the given trick. <br>
+    /// Players have to follow the color of the trick if they can. Wizards and Fools may always be played.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::card::Card;
+    /// # use wizard::wizard::card::CardColor;
+    /// let hand = vec![
+    ///     Card::Number(3, CardColor::RED),
+    ///     Card::Number(8, CardColor::GREEN),
+    ///     Card::Fool,
+    /// ];
+    /// let trick = vec![Card::Number(5, CardColor::RED)];
+    /// let playable = Card::playable_cards(hand, trick);
+    /// assert_eq!(playable, vec![Card::Number(3, CardColor::RED), Card::Fool]);
+    /// ```
+    pub fn playable_cards(hand: Vec<Card>, trick: Vec<Card>) -> Vec<Card> {
+        match Card::get_prio_color(trick) {
+            Some(color)
+                if hand.iter().any(
+                    |card| matches!(card, Card::Number(_, card_color) if *card_color == color),
+                ) =>
+            {
+                Card::filter(hand, color)
+            }
+            _ => hand,
+        }
+    }
+
     /// Returns a `Vec<Card>` with all possible cards in wizard. (sorted)
     ///
     /// # Examples
@@ -282,8 +358,7 @@ impl Card {
     /// ```
     pub fn all_cards() -> Vec<Card> {
         let mut cards: Vec<Card> = Vec::new();
-        let colors: [CardColor; 4] = [CardColor::BLUE, CardColor::GREEN, CardColor::RED, CardColor::YELLOW];
-        for color in colors {
+        for color in CardColor::all_colors() {
             for value in 1..=13 {
                 cards.push(Card::Number(value, color));
             }
@@ -294,10 +369,145 @@ impl Card {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+/// The view a single player has on the cards of the current round. <br>
+/// It is used to figure out which cards are still out and how likely it is to win tricks with the own hand.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::card::Card;
+/// # use wizard::wizard::card::CardColor;
+/// # use wizard::wizard::card::CardCounter;
+/// let counter = CardCounter {
+///     hand: vec![Card::Wizard, Card::Number(3, CardColor::RED)],
+///     played: Vec::new(),
+///     trick: vec![Card::Number(9, CardColor::RED)],
+///     trump_card: Some(Card::Number(5, CardColor::BLUE)),
+///     main_color: Some(CardColor::BLUE),
+///     player_count: 3,
+/// };
+///
+/// assert_eq!(counter.unseen_cards().len(), 56);
+/// assert_eq!(counter.win_probability(Card::Wizard), 1.0);
+/// assert_eq!(counter.win_probability(Card::Number(3, CardColor::RED)), 0.0);
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct CardCounter {
+    /// The cards in the players hand.
+    pub hand: Vec<Card>,
+    /// The cards of all tricks that were completed in this round.
+    pub played: Vec<Card>,
+    /// The cards of the current trick in the order they were played.
+    pub trick: Vec<Card>,
+    /// The card that was turned up to determine the trump. `None` in the last round.
+    pub trump_card: Option<Card>,
+    pub main_color: Option<CardColor>,
+    pub player_count: u32,
+}
+
+impl CardCounter {
+    /// Returns all cards the player has not seen in this round yet. These are the cards that may be in the hands of the other players.
+    pub fn unseen_cards(&self) -> Vec<Card> {
+        let mut unseen = Card::all_cards();
+
+        let seen = self
+            .hand
+            .iter()
+            .chain(self.played.iter())
+            .chain(self.trick.iter())
+            .chain(self.trump_card.iter());
+        for card in seen {
+            if let Some(index) = unseen.iter().position(|e| e == card) {
+                unseen.remove(index);
+            }
+        }
+        unseen
+    }
+
+    /// Returns the probability that the given card wins the current trick if it is played now. <br>
+    /// The cards of the players who still have to play are assumed to be drawn at random from the unseen cards.
+    pub fn win_probability(&self, card: Card) -> f64 {
+        let mut trick = self.trick.clone();
+        trick.push(card);
+        let own_index = trick.len() - 1;
+
+        if Card::winning_index(trick.clone(), self.main_color) != Ok(own_index) {
+            return 0.0;
+        }
+
+        let unseen = self.unseen_cards();
+        let remaining = (self.player_count as usize).saturating_sub(trick.len());
+
+        // A card only stays on top if none of the following cards beats it on its own.
+        let beating = unseen
+            .iter()
+            .filter(|other| {
+                let mut next = trick.clone();
+                next.push(**other);
+                Card::winning_index(next, self.main_color) != Ok(own_index)
+            })
+            .count();
+        let safe = unseen.len() - beating;
+
+        // probability that every remaining player draws one of the safe cards
+        (0..remaining)
+            .map(|i| safe.saturating_sub(i) as f64 / unseen.len().saturating_sub(i).max(1) as f64)
+            .product()
+    }
+
+    /// Returns the expected amount of tricks for the players hand. <br>
+    /// This is the sum of the probabilities of every card winning a trick when it is led, so it is only an estimate.
+    pub fn expected_tricks(&self) -> f64 {
+        let lead = CardCounter {
+            played: self
+                .played
+                .iter()
+                .chain(self.trick.iter())
+                .copied()
+                .collect(),
+            trick: Vec::new(),
+            ..self.clone()
+        };
+
+        self.hand
+            .iter()
+            .map(|card| lead.win_probability(*card))
+            .sum()
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum CardColor {
     BLUE,
     GREEN,
     RED,
     YELLOW,
-}
\ No newline at end of file
+}
+
+impl CardColor {
+    /// Returns the *name* of the given color as a String, in the language set with `i18n::set_language`.
+    pub fn name(&self) -> String {
+        self.name_in(i18n::language())
+    }
+
+    /// Returns the *name* of the given color in the given language.
+    pub fn name_in(&self, language: Language) -> String {
+        let key = match self {
+            CardColor::BLUE => "color.blue",
+            CardColor::GREEN => "color.green",
+            CardColor::RED => "color.red",
+            CardColor::YELLOW => "color.yellow",
+        };
+        i18n::text_in(language, key, &[])
+    }
+
+    /// Returns a `Vec<CardColor>` with all colors in wizard. (sorted)
+    pub fn all_colors() -> Vec<CardColor> {
+        vec![
+            CardColor::BLUE,
+            CardColor::GREEN,
+            CardColor::RED,
+            CardColor::YELLOW,
+        ]
+    }
+}
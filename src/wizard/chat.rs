@@ -0,0 +1,131 @@
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::player::PlayerId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The most characters a chat message may have.
+pub const MAX_LENGTH: usize = 200;
+
+/// A quick reaction that is sent with a single click or key.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Emote {
+    Hello,
+    WellPlayed,
+    Oops,
+    Thinking,
+    Laugh,
+    GoodGame,
+}
+
+impl Emote {
+    /// Returns all emotes in the order they are offered.
+    pub fn all_emotes() -> Vec<Emote> {
+        vec![
+            Emote::Hello,
+            Emote::WellPlayed,
+            Emote::Oops,
+            Emote::Thinking,
+            Emote::Laugh,
+            Emote::GoodGame,
+        ]
+    }
+
+    /// Returns what the emote says, e.g. `"Well played!"`.
+    pub fn text(&self) -> String {
+        let key = match self {
+            Emote::Hello => "emote.hello",
+            Emote::WellPlayed => "emote.well_played",
+            Emote::Oops => "emote.oops",
+            Emote::Thinking => "emote.thinking",
+            Emote::Laugh => "emote.laugh",
+            Emote::GoodGame => "emote.good_game",
+        };
+        i18n::text(key)
+    }
+}
+
+/// Returns the chat message without surrounding whitespace. <br>
+/// `WizardError::InvalidChat` is returned if the message is empty or longer than `MAX_LENGTH` characters.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::chat;
+/// assert_eq!(chat::check(" Hi all ").unwrap(), "Hi all");
+/// assert!(chat::check(" ").is_err());
+/// assert!(chat::check(&"a".repeat(chat::MAX_LENGTH + 1)).is_err());
+/// ```
+pub fn check(text: &str) -> Result<String, WizardError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(WizardError::InvalidChat(i18n::text("chat.empty")));
+    }
+    if text.chars().count() > MAX_LENGTH {
+        return Err(WizardError::InvalidChat(i18n::text_with(
+            "chat.too_long",
+            &[&MAX_LENGTH],
+        )));
+    }
+    Ok(text.to_string())
+}
+
+/// Allows every player a number of chat messages and emotes within a time window.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    pub messages: usize,
+    pub window: Duration,
+    /// When every player sent their latest messages, the oldest first.
+    sent: BTreeMap<PlayerId, VecDeque<Instant>>,
+}
+
+impl Default for RateLimit {
+    /// 5 messages in 10 seconds.
+    fn default() -> Self {
+        RateLimit::new(5, Duration::from_secs(10))
+    }
+}
+
+impl RateLimit {
+    pub fn new(messages: usize, window: Duration) -> Self {
+        RateLimit {
+            messages,
+            window,
+            sent: BTreeMap::new(),
+        }
+    }
+
+    /// Counts a message of the player that is sent at the given time. <br>
+    /// `WizardError::InvalidChat` is returned if the player already sent `messages` messages within the window,
+    /// the message is not counted then.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # use wizard::wizard::chat::RateLimit;
+    /// # use wizard::wizard::player::PlayerId;
+    /// let mut limit = RateLimit::new(2, Duration::from_secs(10));
+    /// let now = Instant::now();
+    /// assert!(limit.count(PlayerId(1), now).is_ok());
+    /// assert!(limit.count(PlayerId(1), now).is_ok());
+    /// assert!(limit.count(PlayerId(1), now).is_err());
+    /// assert!(limit.count(PlayerId(2), now).is_ok());
+    /// assert!(limit.count(PlayerId(1), now + Duration::from_secs(10)).is_ok());
+    /// ```
+    pub fn count(&mut self, player: PlayerId, now: Instant) -> Result<(), WizardError> {
+        let sent = self.sent.entry(player).or_default();
+        while sent
+            .front()
+            .is_some_and(|e| now.saturating_duration_since(*e) >= self.window)
+        {
+            sent.pop_front();
+        }
+        if sent.len() >= self.messages {
+            return Err(WizardError::InvalidChat(i18n::text("chat.too_fast")));
+        }
+        sent.push_back(now);
+        Ok(())
+    }
+}
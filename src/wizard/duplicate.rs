@@ -0,0 +1,176 @@
+use crate::wizard::card::Card;
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::record::GameRecord;
+use crate::wizard::report::Report;
+use crate::wizard::rules::Schedule;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The shuffled decks of a whole game, generated from a seed. Every table of a duplicate game plays the same deal set,
+/// so the players on the same seat get the same cards at every table. Every round has a deck of its own, even if a
+/// schedule repeats the amount of cards.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::duplicate::DealSet;
+/// # use wizard::wizard::rules::Schedule;
+/// let deals = DealSet::generate(42, 4, &Schedule::UpAndDown(3))?;
+///
+/// assert_eq!(deals.decks.len(), 5);
+/// assert_ne!(deals.deck(1)?, deals.deck(5)?);
+/// assert_eq!(deals, DealSet::generate(42, 4, &Schedule::UpAndDown(3))?);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct DealSet {
+    pub seed: u64,
+    pub player_count: u32,
+    /// The deck of every round, the first one is used for the first round.
+    pub decks: Vec<Vec<Card>>,
+}
+
+impl DealSet {
+    /// Shuffles `Card::all_cards()` once for every round of the schedule in a game with the given amount of players. <br>
+    /// `WizardError::InvalidPlayerCount` is returned if the amount of players is not between 3 and 6, the errors of
+    /// `Schedule::rounds` if the schedule doesn't fit the players.
+    pub fn generate(
+        seed: u64,
+        player_count: u32,
+        schedule: &Schedule,
+    ) -> Result<Self, WizardError> {
+        if !(3..=6).contains(&player_count) {
+            return Err(WizardError::InvalidPlayerCount(player_count as usize));
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        // only the amount of rounds is needed, which doesn't depend on chance
+        let rounds = schedule.rounds(player_count as usize, &mut StdRng::seed_from_u64(seed))?;
+        let decks = (0..rounds.len())
+            .map(|_| {
+                let mut cards = Card::all_cards();
+                cards.shuffle(&mut rng);
+                cards
+            })
+            .collect();
+
+        Ok(DealSet {
+            seed,
+            player_count,
+            decks,
+        })
+    }
+
+    /// Returns the deck for the round with the given number, starting with 1. <br>
+    /// `WizardError::InvalidDealSet` is returned if the deal set has no deck for that round.
+    pub fn deck(&self, round_number: u32) -> Result<Vec<Card>, WizardError> {
+        match round_number
+            .checked_sub(1)
+            .and_then(|i| self.decks.get(i as usize))
+        {
+            Some(deck) => Ok(deck.clone()),
+            None => Err(WizardError::InvalidDealSet {
+                player_count: self.player_count,
+                round_count: self.decks.len(),
+            }),
+        }
+    }
+
+    /// Writes the deal set as JSON to the given path.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Reads a deal set from a JSON file at the given path. <br>
+    /// An error of the kind `InvalidData` is returned if the amount of players is not between 3 and 6 or a deck is not
+    /// a shuffled `Card::all_cards()`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let deal_set: DealSet = serde_json::from_str(&json)?;
+
+        let all_cards = Card::all_cards();
+        let complete = |deck: &Vec<Card>| {
+            deck.len() == all_cards.len()
+                && all_cards.iter().all(|card| {
+                    let count = |cards: &[Card]| cards.iter().filter(|e| *e == card).count();
+                    count(deck) == count(&all_cards)
+                })
+        };
+        if !(3..=6).contains(&deal_set.player_count) || !deal_set.decks.iter().all(complete) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                WizardError::InvalidDealSet {
+                    player_count: deal_set.player_count,
+                    round_count: deal_set.decks.len(),
+                },
+            ));
+        }
+        Ok(deal_set)
+    }
+}
+
+/// The final scores of a duplicate game, compared seat against seat. <br>
+/// Every player is measured against the average score of the players who had the same seat at the other tables.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DuplicateResult {
+    /// The names and final scores of every seat, one entry per table.
+    pub seats: Vec<Vec<(String, i32)>>,
+}
+
+impl DuplicateResult {
+    /// Compares the records of all tables that played the same deal set. The seats are taken from the order of the players in each record.
+    pub fn new(records: &[GameRecord]) -> Self {
+        let mut seats: Vec<Vec<(String, i32)>> = Vec::new();
+
+        for record in records {
+            let report = Report::new(record);
+            for (seat, player) in report.players.iter().enumerate() {
+                if seats.len() <= seat {
+                    seats.push(Vec::new());
+                }
+                seats[seat].push((player.name.clone(), player.score));
+            }
+        }
+
+        DuplicateResult { seats }
+    }
+
+    /// Returns the score of every player minus the average score of the seat, per seat and table.
+    pub fn differences(&self) -> Vec<Vec<(String, f64)>> {
+        self.seats
+            .iter()
+            .map(|seat| {
+                let average = seat.iter().map(|e| e.1).sum::<i32>() as f64 / seat.len() as f64;
+                seat.iter()
+                    .map(|(name, score)| (name.clone(), *score as f64 - average))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the comparison of all seats as printable text.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (seat, (scores, differences)) in self.seats.iter().zip(self.differences()).enumerate() {
+            let entries: Vec<String> = scores
+                .iter()
+                .zip(differences)
+                .map(|((name, score), (_, difference))| {
+                    format!("{} {} ({:+.1})", name, score, difference)
+                })
+                .collect();
+            out.push_str(&format!(
+                "{}\n",
+                i18n::text_with("duplicate.seat", &[&(seat + 1), &entries.join(", ")])
+            ));
+        }
+        out
+    }
+}
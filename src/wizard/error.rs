@@ -0,0 +1,124 @@
+use crate::wizard::card::Card;
+use crate::wizard::game::Phase;
+use crate::wizard::i18n;
+use inquire::InquireError;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong in the library, from rule violations to cancelled prompts.
+#[derive(PartialEq, Clone, Debug)]
+pub enum WizardError {
+    /// The winner of a trick without cards can't be determined.
+    EmptyTrick,
+    /// Every player gets between 1 and `max` cards.
+    InvalidDealSize {
+        amount: u32,
+        max: u32,
+    },
+    /// A deal set doesn't fit the game, it is for other amounts of players or rounds.
+    InvalidDealSet {
+        player_count: u32,
+        round_count: usize,
+    },
+    /// A round schedule that can't be read or has no rounds.
+    InvalidSchedule(String),
+    /// A game needs 3 to 6 players.
+    InvalidPlayerCount(usize),
+    PlayerNotSeated(String),
+    /// A player in a list such as `"Ann,bot:hard"` can't be read.
+    InvalidPlayer(String),
+    UnknownRuleSet(String),
+    IllegalPlay {
+        card: Card,
+        playable: Vec<Card>,
+    },
+    BidOutOfRange {
+        stitches: u32,
+        options: Vec<u32>,
+    },
+    /// The action is not allowed in the given phase.
+    WrongPhase(Phase),
+    TakeBackDisabled,
+    NothingToTakeBack,
+    /// The user pressed Esc or Ctrl-C in a prompt.
+    InputCancelled,
+    /// A prompt could not be shown or read.
+    Input(String),
+    /// A network connection failed or sent something unexpected.
+    Network(String),
+    /// A lobby request that can't be granted, such as joining a full room.
+    Room(String),
+    /// A chat message that is empty, too long or sent too fast.
+    InvalidChat(String),
+    /// The program of an external bot could not be started.
+    BotProcess(String),
+}
+
+impl fmt::Display for WizardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            WizardError::EmptyTrick => i18n::text("error.empty_trick"),
+            WizardError::InvalidDealSize { amount, max } => {
+                i18n::text_with("error.invalid_deal_size", &[max, amount])
+            }
+            WizardError::InvalidDealSet {
+                player_count,
+                round_count,
+            } => i18n::text_with("error.invalid_deal_set", &[player_count, round_count]),
+            WizardError::InvalidSchedule(schedule) => {
+                i18n::text_with("error.invalid_schedule", &[&format!("{:?}", schedule)])
+            }
+            WizardError::InvalidPlayerCount(count) => {
+                i18n::text_with("error.invalid_player_count", &[count])
+            }
+            WizardError::PlayerNotSeated(name) => {
+                i18n::text_with("error.player_not_seated", &[name])
+            }
+            WizardError::InvalidPlayer(entry) => {
+                i18n::text_with("error.invalid_player", &[&format!("{:?}", entry)])
+            }
+            WizardError::UnknownRuleSet(name) => {
+                i18n::text_with("error.unknown_rule_set", &[&format!("{:?}", name)])
+            }
+            WizardError::IllegalPlay { card, playable } => {
+                let names: Vec<String> = playable.iter().map(|e| e.name()).collect();
+                i18n::text_with("error.illegal_play", &[&card.name(), &names.join(", ")])
+            }
+            WizardError::BidOutOfRange { stitches, options } => i18n::text_with(
+                "error.bid_out_of_range",
+                &[&format!("{:?}", options), stitches],
+            ),
+            WizardError::WrongPhase(phase) => {
+                i18n::text_with("error.wrong_phase", &[&format!("{:?}", phase)])
+            }
+            WizardError::TakeBackDisabled => i18n::text("error.take_back_disabled"),
+            WizardError::NothingToTakeBack => i18n::text("error.nothing_to_take_back"),
+            WizardError::InputCancelled => i18n::text("error.input_cancelled"),
+            WizardError::Input(message) => i18n::text_with("error.input", &[message]),
+            WizardError::Network(message) => i18n::text_with("error.network", &[message]),
+            WizardError::Room(message) => message.clone(),
+            WizardError::InvalidChat(message) => message.clone(),
+            WizardError::BotProcess(message) => i18n::text_with("error.bot_process", &[message]),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl std::error::Error for WizardError {}
+
+impl From<io::Error> for WizardError {
+    fn from(error: io::Error) -> Self {
+        WizardError::Network(error.to_string())
+    }
+}
+
+impl From<InquireError> for WizardError {
+    fn from(error: InquireError) -> Self {
+        match error {
+            InquireError::OperationCanceled | InquireError::OperationInterrupted => {
+                WizardError::InputCancelled
+            }
+            e => WizardError::Input(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use crate::wizard::bot::Difficulty;
+use crate::wizard::card::{Card, CardCounter};
+use crate::wizard::duplicate::DealSet;
+use crate::wizard::error::WizardError;
+use crate::wizard::game::{Action, Game, Phase};
+use crate::wizard::i18n;
+use crate::wizard::net::{ClientMessage, ServerMessage, TimeoutPolicy};
+use crate::wizard::player::{Player, PlayerId};
+use crate::wizard::record::Event;
+use crate::wizard::rules::{RuleSet, Schedule};
+use crate::wizard::view::View;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long an external bot has for every action unless its `timeout` is changed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often an external bot may answer a turn with an illegal action before the action is chosen for it.
+pub const MAX_ATTEMPTS: usize = 3;
+
+/// Something an external bot did wrong.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum Fault {
+    /// The bot exited, closed its input or output or sent a line that isn't a message of the protocol.
+    /// It doesn't act anymore for the rest of the game.
+    Crashed { reason: String },
+    /// The bot didn't answer its turn in time.
+    TimedOut,
+    /// The bot answered with an action that isn't one of the options of its view.
+    IllegalMove { action: Action },
+}
+
+impl Fault {
+    pub fn description(&self) -> String {
+        match self {
+            Fault::Crashed { reason } => i18n::text_with("external.crashed", &[reason]),
+            Fault::TimedOut => i18n::text("external.timed_out"),
+            Fault::IllegalMove { action } => {
+                i18n::text_with("external.illegal_move", &[&format!("{:?}", action)])
+            }
+        }
+    }
+}
+
+/// A computer controlled player whose decisions are made by another program. <br>
+/// The program is sent the messages of the network protocol on its standard input, one JSON message per line, and
+/// answers on its standard output in the same way: it gets `Welcome` with its player id, `Events` whenever something
+/// happens, `Turn` with its view whenever it has to act and `GameOver` at the end. It answers every `Turn` with `Act`
+/// and one of the options of the view. After `Rejected` it gets the turn again, after `TimedOut` the action was
+/// chosen for it. Every game starts a new process, which may exit after `GameOver`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::collections::BTreeMap;
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::external::{self, ExternalBot};
+/// # use wizard::wizard::game::Game;
+/// # use wizard::wizard::player::Player;
+/// let mut game = Game::default();
+/// game.players.push(Player::external("python3 greedy.py"));
+/// for number in 1..=2 {
+///     game.players.push(Player::bot(Difficulty::Hard, number));
+/// }
+///
+/// let mut bots = BTreeMap::new();
+/// bots.insert(game.players[0].id, ExternalBot::spawn("python3 greedy.py")?);
+/// external::play(&mut game, &mut bots)?;
+/// println!("{:?}", bots[&game.players[0].id].faults);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+pub struct ExternalBot {
+    /// How long the bot has for every action.
+    pub timeout: Duration,
+    /// How the action is chosen when the bot takes too long, breaks the rules too often or crashed.
+    pub on_fault: TimeoutPolicy,
+    /// The actions the bot chose itself.
+    pub moves: u32,
+    /// Everything the bot did wrong, in order.
+    pub faults: Vec<Fault>,
+    writer: Box<dyn Write + Send>,
+    /// The lines the bot wrote, read in their own thread so that waiting for them can time out.
+    lines: Receiver<String>,
+    child: Option<Child>,
+    crashed: bool,
+}
+
+impl ExternalBot {
+    /// Starts the program of the command, which is split at whitespace into the program and its arguments. <br>
+    /// `WizardError::BotProcess` is returned if the program can't be started.
+    pub fn spawn(command: &str) -> Result<Self, WizardError> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| WizardError::BotProcess(i18n::text("external.empty_command")))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| WizardError::BotProcess(format!("{}: {}", command, e)))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let mut bot = ExternalBot::with_streams(stdout, stdin);
+        bot.child = Some(child);
+        Ok(bot)
+    }
+
+    /// Returns a bot that reads the answers of a program from `reader` and writes the messages for it to `writer`,
+    /// e.g. a bot running in a thread of the same process.
+    pub fn with_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        ExternalBot {
+            timeout: DEFAULT_TIMEOUT,
+            on_fault: TimeoutPolicy::AutoPlay,
+            moves: 0,
+            faults: Vec::new(),
+            writer: Box::new(writer),
+            lines,
+            child: None,
+            crashed: false,
+        }
+    }
+
+    /// Sends a message to the bot. A bot that can't be written to crashed.
+    pub fn send(&mut self, message: &ServerMessage) {
+        if self.crashed {
+            return;
+        }
+        let written = serde_json::to_string(message)
+            .map_err(|e| e.to_string())
+            .and_then(|line| {
+                writeln!(self.writer, "{}", line)
+                    .and_then(|_| self.writer.flush())
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = written {
+            self.crash(i18n::text_with("external.not_writable", &[&e]));
+        }
+    }
+
+    /// Asks the bot for the action of the given player, whose turn it is. If the bot takes longer than `timeout`,
+    /// answers with an illegal action `MAX_ATTEMPTS` times or crashed, the fault is recorded and the action is
+    /// chosen as `on_fault` says. <br>
+    /// `WizardError::WrongPhase` is returned if there is nothing to choose, `WizardError::PlayerNotSeated` if the
+    /// player is not part of the game.
+    pub fn act(&mut self, game: &mut Game, id: PlayerId) -> Result<Action, WizardError> {
+        let options = game.legal_actions();
+        let deadline = Instant::now() + self.timeout;
+
+        // answers that came too late for earlier turns don't count for this one
+        while self.lines.try_recv().is_ok() {}
+        let mut attempts = 0;
+        while !self.crashed && attempts < MAX_ATTEMPTS {
+            let view = Box::new(View::new(game, Some(id)));
+            self.send(&ServerMessage::Turn { view });
+            match self.receive_action(deadline) {
+                Some(action) if options.contains(&action) => {
+                    self.moves += 1;
+                    return Ok(action);
+                }
+                Some(action) => {
+                    self.faults.push(Fault::IllegalMove { action });
+                    attempts += 1;
+                    let reason =
+                        i18n::text_with("external.not_an_option", &[&format!("{:?}", action)]);
+                    self.send(&ServerMessage::Rejected { reason });
+                }
+                None if self.crashed => {}
+                None => {
+                    self.faults.push(Fault::TimedOut);
+                    break;
+                }
+            }
+        }
+
+        let action = self.fallback(game, id)?;
+        self.send(&ServerMessage::TimedOut { action });
+        Ok(action)
+    }
+
+    /// Waits until the deadline for the bot to act. Anything else it sends is ignored.
+    fn receive_action(&mut self, deadline: Instant) -> Option<Action> {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    let reason = match self.child.as_mut().map(|e| e.try_wait()) {
+                        Some(Ok(Some(status))) => i18n::text_with("external.exited", &[&status]),
+                        _ => i18n::text("external.closed_output"),
+                    };
+                    self.crash(reason);
+                    return None;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ClientMessage>(&line) {
+                Ok(ClientMessage::Act { action }) => return Some(action),
+                Ok(_) => {}
+                Err(e) => {
+                    self.crash(i18n::text_with(
+                        "external.no_message",
+                        &[&format!("{:?}", line), &e],
+                    ));
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Returns the action that is played for the bot when it didn't choose one.
+    fn fallback(&self, game: &mut Game, id: PlayerId) -> Result<Action, WizardError> {
+        let Some(&first) = game.legal_actions().first() else {
+            return Err(WizardError::WrongPhase(game.phase));
+        };
+        let difficulty = match self.on_fault {
+            TimeoutPolicy::AutoPlay => return Ok(first),
+            TimeoutPolicy::Bot(difficulty) => difficulty,
+        };
+        let index = game
+            .players
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| {
+                WizardError::PlayerNotSeated(i18n::text_with("player.unknown", &[&id.0]))
+            })?;
+        let bot = game.players[index].bot.replace(difficulty);
+        let action = game.next_action();
+        game.players[index].bot = bot;
+        match action? {
+            Some((_, action)) => Ok(action),
+            None => Err(WizardError::WrongPhase(game.phase)),
+        }
+    }
+
+    fn crash(&mut self, reason: String) {
+        if !self.crashed {
+            self.crashed = true;
+            self.faults.push(Fault::Crashed { reason });
+        }
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Plays the game without printing anything, like `Simulation::run`. The players in `bots` are played by their
+/// external bots, everyone else has to be a built-in bot. The bots are sent every event as their player may see it.
+pub fn play(
+    game: &mut Game,
+    bots: &mut BTreeMap<PlayerId, ExternalBot>,
+) -> Result<(), WizardError> {
+    for (id, bot) in bots.iter_mut() {
+        bot.send(&ServerMessage::Welcome {
+            player: *id,
+            token: String::new(),
+        });
+    }
+
+    loop {
+        let action = match game.phase {
+            Phase::Finished => break,
+            Phase::Setup => Action::Start,
+            Phase::Dealing => Action::Deal,
+            Phase::RoundScored => Action::NextRound,
+            _ => {
+                let id = game.next_player().unwrap();
+                match bots.get_mut(&id) {
+                    Some(bot) => bot.act(game, id)?,
+                    None => game.next_action()?.unwrap().1,
+                }
+            }
+        };
+
+        let events = game.apply(action)?;
+        if !events.is_empty() {
+            for (id, bot) in bots.iter_mut() {
+                let events = events.iter().map(|e| View::redact(e, Some(*id))).collect();
+                bot.send(&ServerMessage::Events { events });
+            }
+        }
+    }
+
+    let scores: Vec<(String, i32)> = game
+        .players
+        .iter()
+        .map(|e| (e.name.clone(), game.score(e.id)))
+        .collect();
+    for bot in bots.values_mut() {
+        bot.send(&ServerMessage::GameOver {
+            scores: scores.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Starts an external bot for every player of the game who has a command.
+pub fn spawn_all(game: &Game) -> Result<BTreeMap<PlayerId, ExternalBot>, WizardError> {
+    let mut bots = BTreeMap::new();
+    for player in &game.players {
+        if let Some(command) = &player.external {
+            bots.insert(player.id, ExternalBot::spawn(command)?);
+        }
+    }
+    Ok(bots)
+}
+
+/// The outcome of `check`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Conformance {
+    pub games: u32,
+    /// The actions the bot chose itself.
+    pub moves: u32,
+    /// Everything the bot did wrong together with the number of the game, starting at 1.
+    pub faults: Vec<(u32, Fault)>,
+}
+
+impl Conformance {
+    pub fn passed(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    /// Returns the outcome as printable text.
+    pub fn text(&self) -> String {
+        let mut out = format!(
+            "{}\n",
+            i18n::text_with(
+                "external.summary",
+                &[&self.games, &self.moves, &self.faults.len()]
+            )
+        );
+        for (game, fault) in &self.faults {
+            out.push_str(&format!(
+                "{}\n",
+                i18n::text_with("external.fault", &[game, &fault.description()])
+            ));
+        }
+        let key = match self.passed() {
+            true => "external.passed",
+            false => "external.failed",
+        };
+        out.push_str(&format!("{}\n", i18n::text(key)));
+        out
+    }
+}
+
+/// Lets a bot play `games` short games against medium bots, with 3 to 6 players and the bot at every seat, and
+/// returns everything it did wrong. `start` is called for a new bot before every game. The cards are the same every
+/// time, so a check can be repeated. <br>
+/// Errors of `start` are returned.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use wizard::wizard::external::{self, ExternalBot};
+/// let conformance = external::check(|| ExternalBot::spawn("./my-bot"), 20)?;
+/// print!("{}", conformance.text());
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+pub fn check(
+    mut start: impl FnMut() -> Result<ExternalBot, WizardError>,
+    games: u32,
+) -> Result<Conformance, WizardError> {
+    let mut conformance = Conformance {
+        games,
+        moves: 0,
+        faults: Vec::new(),
+    };
+    for number in 1..=games {
+        let player_count = 3 + (number - 1) as usize % 4;
+        let seat = (number - 1) as usize % player_count;
+        let mut players: Vec<Player> = (1..player_count as u32)
+            .map(|e| Player::bot(Difficulty::Medium, e))
+            .collect();
+        players.insert(seat, Player::with_name("Candidate".to_string()));
+        let id = players[seat].id;
+
+        let rules = RuleSet {
+            name: "Check".to_string(),
+            schedule: Schedule::List(vec![1, 3, 10]),
+        };
+        let mut game = Game {
+            deal_set: Some(DealSet::generate(
+                number as u64,
+                player_count as u32,
+                &rules.schedule,
+            )?),
+            rules,
+            players,
+            ..Game::default()
+        };
+        let mut bots = BTreeMap::new();
+        bots.insert(id, start()?);
+        play(&mut game, &mut bots)?;
+
+        let bot = &bots[&id];
+        conformance.moves += bot.moves;
+        conformance
+            .faults
+            .extend(bot.faults.iter().map(|e| (number, e.clone())));
+    }
+    Ok(conformance)
+}
+
+/// The reference implementation of an external bot. It reads the messages of the server from `input` and answers
+/// every turn on `output` like a hard built-in bot, until the game is over or the input ends. <br>
+/// `WizardError::Network` is returned if a line is no message of the protocol or the output fails.
+pub fn reference_bot(input: impl BufRead, mut output: impl Write) -> Result<(), WizardError> {
+    // the cards played in this round, which the view doesn't list
+    let mut played = Vec::new();
+    for line in input.lines() {
+        let message: ServerMessage =
+            serde_json::from_str(&line?).map_err(|e| WizardError::Network(e.to_string()))?;
+        match message {
+            ServerMessage::Events { events } => {
+                for event in events {
+                    match event {
+                        Event::RoundStarted { .. } => played.clear(),
+                        Event::Played { card, .. } => played.push(card),
+                        _ => {}
+                    }
+                }
+            }
+            ServerMessage::Turn { view } if !view.options.is_empty() => {
+                let action = reference_action(&view, &played);
+                let answer = serde_json::to_string(&ClientMessage::Act { action })
+                    .map_err(|e| WizardError::Network(e.to_string()))?;
+                writeln!(output, "{}", answer)?;
+                output.flush()?;
+            }
+            ServerMessage::GameOver { .. } => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Chooses an action of the view like a hard bot. `played` are the cards played in the round so far.
+fn reference_action(view: &View, played: &[Card]) -> Action {
+    let me = view.viewer.unwrap_or(PlayerId(0));
+    let counter = CardCounter {
+        hand: view.hand.clone(),
+        played: played[..played.len().saturating_sub(view.trick.len())].to_vec(),
+        trick: view.trick.iter().map(|e| e.1).collect(),
+        trump_card: view.trump_card,
+        main_color: view.main_color,
+        player_count: view.players.len() as u32,
+    };
+
+    let bot = Difficulty::Hard;
+    match view.options[0] {
+        Action::ChooseColor(_) => Action::ChooseColor(bot.choose_color(&view.hand)),
+        Action::Guess(_) => {
+            let options: Vec<u32> = view
+                .options
+                .iter()
+                .filter_map(|e| match e {
+                    Action::Guess(stitches) => Some(*stitches),
+                    _ => None,
+                })
+                .collect();
+            Action::Guess(bot.guess_stitches(&counter, &options))
+        }
+        _ => {
+            let cards: Vec<Card> = view
+                .options
+                .iter()
+                .filter_map(|e| match e {
+                    Action::Play(card) => Some(*card),
+                    _ => None,
+                })
+                .collect();
+            let guess = view.guesses.get(&me).copied().unwrap_or(0);
+            let stitches = view.stitches.get(&me).copied().unwrap_or(0);
+            Action::Play(bot.play_card(&counter, &cards, guess, stitches))
+        }
+    }
+}
@@ -1,60 +1,668 @@
-use rand::thread_rng;
+use crate::wizard::advisor::{BidAdvice, PlayAdvice};
+use crate::wizard::card::{Card, CardColor, CardCounter};
+use crate::wizard::duplicate::DealSet;
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::player::{Player, PlayerId};
+use crate::wizard::record::{Event, GameRecord};
+use crate::wizard::round::{Hands, Round};
+use crate::wizard::rules::RuleSet;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use crate::wizard::card::Card;
-use crate::wizard::player::Player;
+use rand::{thread_rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// The phases of a game. The player whose turn it is is referred to by id.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Phase {
+    /// The players take their seats, the game starts with `Action::Start`.
+    #[default]
+    Setup,
+    /// The next round starts with `Action::Deal`.
+    Dealing,
+    /// A Wizard was revealed as the trump card, the dealer chooses the main color.
+    TrumpSelection,
+    Bidding {
+        next_player: PlayerId,
+    },
+    /// `trick` is the index of the current trick in the round.
+    Playing {
+        trick: usize,
+        next_player: PlayerId,
+    },
+    /// The next round is started with `Action::NextRound`.
+    RoundScored,
+    Finished,
+}
+
+/// Everything that can be done in a game. Which actions are allowed depends on the `Phase`.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Start,
+    Deal,
+    ChooseColor(CardColor),
+    Guess(u32),
+    Play(Card),
+    /// The given player takes back their last guess or card of the current round. Only allowed in casual games and
+    /// while no one else moved since.
+    TakeBack(PlayerId),
+    NextRound,
+}
+
+/// A game of Wizard as a state machine. Every front end drives the game with `apply`, which checks the action
+/// against the rules, moves the game to the next `Phase` and returns the `Event`s that happened.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::error::WizardError;
+/// # use wizard::wizard::game::{Action, Game, Phase};
+/// # use wizard::wizard::player::Player;
+/// let mut game = Game::default();
+/// for number in 1..=3 {
+///     game.players.push(Player::bot(Difficulty::Hard, number));
+/// }
+///
+/// assert_eq!(game.apply(Action::Deal), Err(WizardError::WrongPhase(Phase::Setup)));
+/// game.apply(Action::Start).unwrap();
+/// game.apply(Action::Deal).unwrap();
+/// let first = game.players[0].id;
+/// assert!(matches!(game.phase, Phase::Bidding { next_player } if next_player == first) || game.phase == Phase::TrumpSelection);
+/// ```
+#[derive(Default)]
 pub struct Game {
     pub players: Vec<Player>,
+    pub record: GameRecord,
+    /// The decks of a duplicate game. The cards are shuffled at random if there is none.
+    pub deal_set: Option<DealSet>,
+    /// Whether humans can take back their last guess or card in this game, which is the case in casual games.
+    pub undo: bool,
+    pub rules: RuleSet,
+    /// The amount of cards of every round, taken from the schedule of the rules when the game starts.
+    pub rounds: Vec<u32>,
+    pub phase: Phase,
+    /// The number of the current or last round, 0 before the first round.
+    pub round_number: u32,
+    /// The current round, from dealing until the next round is started.
+    pub round: Option<Round>,
+    /// The total score of every player.
+    pub scores: BTreeMap<PlayerId, i32>,
+    /// The seat of the dealer of the current or last round. The last seat deals the first round.
+    pub dealer: usize,
 }
 
 impl Game {
-    pub fn shift(&mut self) {
-        self.players.rotate_right(1);
+    /// Returns the seat of the player with the given name. <br>
+    /// `WizardError::PlayerNotSeated` is returned if the player is not part of the game.
+    pub fn seat_of(&self, name: &str) -> Result<usize, WizardError> {
+        self.players
+            .iter()
+            .position(|e| e.name == name)
+            .ok_or_else(|| WizardError::PlayerNotSeated(name.to_string()))
     }
 
-    pub fn shift_till(&mut self, player: Player) {
-        while self.players[0] != player {
-            self.shift();
-        }
+    /// Returns the player with the given id. <br>
+    /// `WizardError::PlayerNotSeated` is returned if the player is not part of the game.
+    pub fn player(&self, id: PlayerId) -> Result<&Player, WizardError> {
+        self.players.iter().find(|e| e.id == id).ok_or_else(|| {
+            WizardError::PlayerNotSeated(i18n::text_with("player.unknown", &[&id.0]))
+        })
+    }
+
+    /// Returns the total score of the given player.
+    pub fn score(&self, id: PlayerId) -> i32 {
+        self.scores.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Returns the seat left of the given one.
+    pub fn next_seat(&self, seat: usize) -> usize {
+        (seat + 1) % self.players.len()
     }
 
     pub fn round_limit(&self) -> u32 {
-        60 / self.players.len() as u32
+        60u32.checked_div(self.players.len() as u32).unwrap_or(0)
     }
 
-    pub fn assign_cards(&mut self, amount: u32) {
-        if amount <= 0 || amount >= 21 {
-            panic!("Invalid amount of cards to assign? Allowed is anything between 1 and 60/player_count = {}. Provided was {}.", self.round_limit(), amount)
+    /// Deals the given amount of cards to every player in seating order and returns the hands together with the card that is
+    /// revealed to determine the trump. <br>
+    /// No card is revealed if all cards were dealt. If the game has a deal set, its deck for the round with the given number is used. <br>
+    /// `WizardError::InvalidDealSize` is returned if the amount is not between 1 and 60/player_count,
+    /// `WizardError::InvalidDealSet` if the deal set has no deck for the round.
+    pub fn assign_cards(
+        &self,
+        round_number: u32,
+        amount: u32,
+    ) -> Result<(Hands, Option<Card>), WizardError> {
+        if amount == 0 || amount > self.round_limit() {
+            return Err(WizardError::InvalidDealSize {
+                amount,
+                max: self.round_limit(),
+            });
         }
 
-        // generate cards
-        let mut cards = Card::all_cards();
-        // randomize cards
-        cards.shuffle(&mut thread_rng());
+        let mut cards = match &self.deal_set {
+            Some(deal_set) => deal_set.deck(round_number)?,
+            None => {
+                // generate cards
+                let mut cards = Card::all_cards();
+                // randomize cards
+                cards.shuffle(&mut thread_rng());
+                cards
+            }
+        };
+
+        // assign new cards
+        let hands = self
+            .players
+            .iter()
+            .map(|player| (player.id, cards.drain(..amount as usize).collect()))
+            .collect();
+
+        Ok((hands, cards.first().copied()))
+    }
 
-        // empty player cards
-        for player in &mut self.players {
-            player.cards.clear()
+    pub fn stitch_options(max: u32, current_count: u32, is_last: bool) -> Vec<u32> {
+        (0..=max)
+            .filter(|x| !is_last || current_count + x != max)
+            .collect()
+    }
+
+    /// Returns the points a player gets for a round. <br>
+    /// A correct guess is worth 20 points plus 10 points per stitch, every stitch too many or too few costs 10 points.
+    pub fn points(guess_stitches: u32, actual_stitches: u32) -> i32 {
+        if guess_stitches == actual_stitches {
+            20 + 10 * actual_stitches as i32
+        } else {
+            -10 * guess_stitches.abs_diff(actual_stitches) as i32
         }
+    }
 
-        // assign new cards
-        for player in &mut self.players {
-            for _ in 1..=amount {
-                player.cards.push(cards[0])
+    /// Applies the given action and returns the events that happened. The events are also added to the record,
+    /// the events of a move that is taken back are removed from it. <br>
+    /// Actions that break the rules are rejected and don't change the game.
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, WizardError> {
+        let events = match (self.phase, action) {
+            (Phase::Setup, Action::Start) => self.start()?,
+            (Phase::Dealing, Action::Deal) => self.deal()?,
+            (Phase::TrumpSelection, Action::ChooseColor(color)) => self.select_color(color),
+            (Phase::Bidding { .. }, Action::Guess(stitches)) => self.guess(stitches)?,
+            (Phase::Playing { .. }, Action::Play(card)) => self.play_card(card)?,
+            (
+                Phase::Bidding { .. } | Phase::Playing { .. } | Phase::RoundScored,
+                Action::TakeBack(player),
+            ) => self.take_back_move(player)?,
+            (Phase::RoundScored, Action::NextRound) => self.next_round(),
+            (phase, _) => return Err(WizardError::WrongPhase(phase)),
+        };
+
+        self.record.events.extend(events.iter().cloned());
+        Ok(events)
+    }
+
+    fn start(&mut self) -> Result<Vec<Event>, WizardError> {
+        if !(3..=6).contains(&self.players.len()) {
+            return Err(WizardError::InvalidPlayerCount(self.players.len()));
+        }
+
+        // all tables of a duplicate game play the same rounds
+        let seed = match &self.deal_set {
+            Some(deal_set) => deal_set.seed,
+            None => rand::random(),
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rounds = self.rules.schedule.rounds(self.players.len(), &mut rng)?;
+        if let Some(deal_set) = &self.deal_set {
+            if deal_set.player_count as usize != self.players.len()
+                || deal_set.decks.len() < rounds.len()
+            {
+                return Err(WizardError::InvalidDealSet {
+                    player_count: deal_set.player_count,
+                    round_count: deal_set.decks.len(),
+                });
             }
         }
+        self.rounds = rounds;
+
+        self.phase = Phase::Dealing;
+        self.dealer = self.players.len() - 1;
+        self.scores = self.players.iter().map(|e| (e.id, 0)).collect();
+        Ok(vec![Event::GameStarted {
+            players: self.players.clone(),
+        }])
     }
 
-    pub fn stitch_options(max: u32, current_count: u32, is_last: bool) -> Vec<u32> {
+    fn deal(&mut self) -> Result<Vec<Event>, WizardError> {
+        // the player left of the last dealer deals
+        let dealer = match self.round_number {
+            0 => self.dealer,
+            _ => self.next_seat(self.dealer),
+        };
+        let round_number = self.round_number + 1;
 
-        let mut out: Vec<u32> = (1..=max).collect();
+        // nothing changes until the cards are dealt
+        let (hands, trump_card) =
+            self.assign_cards(round_number, self.rounds[round_number as usize - 1])?;
+        self.dealer = dealer;
+        self.round_number = round_number;
+        let main_color = match trump_card {
+            Some(Card::Number(_, color)) => Some(color),
+            _ => None,
+        };
+        self.round = Some(Round::new(
+            self.round_number,
+            hands,
+            self.players[self.dealer].id,
+            trump_card,
+            main_color,
+        )?);
 
-        return out.iter().filter(|x| x != max - current_count).collect();
+        if trump_card == Some(Card::Wizard) {
+            self.phase = Phase::TrumpSelection;
+            Ok(Vec::new())
+        } else {
+            Ok(self.start_bidding())
+        }
     }
-}
 
-impl Default for Game {
-    fn default() -> Self {
-        Game { players: vec![] }
+    fn select_color(&mut self, color: CardColor) -> Vec<Event> {
+        self.round.as_mut().unwrap().main_color = Some(color);
+        self.start_bidding()
+    }
+
+    fn start_bidding(&mut self) -> Vec<Event> {
+        let round = self.round.as_ref().unwrap();
+        self.phase = self.round_phase();
+        vec![Event::RoundStarted {
+            round: round.number,
+            hands: round
+                .bidding_order()
+                .into_iter()
+                .map(|player| (player, round.hands[&player].clone()))
+                .collect(),
+            trump_card: round.trump_card,
+            main_color: round.main_color,
+        }]
+    }
+
+    fn guess(&mut self, stitches: u32) -> Result<Vec<Event>, WizardError> {
+        let round = self.round.as_mut().unwrap();
+        let player = round.next_player().unwrap_or(round.dealer);
+        round.guess(stitches)?;
+        self.phase = self.round_phase();
+        Ok(vec![Event::Guessed { player, stitches }])
+    }
+
+    fn play_card(&mut self, card: Card) -> Result<Vec<Event>, WizardError> {
+        let round = self.round.as_mut().unwrap();
+        let player = round.next_player().unwrap_or(round.dealer);
+        let winner = round.play(card)?;
+        let mut events = vec![Event::Played { player, card }];
+        if let Some(winner) = winner {
+            events.push(Event::TrickWon { player: winner });
+        }
+
+        if round.is_finished() {
+            let mut points = Vec::new();
+            for player in &round.seats {
+                let round_points = round.points(*player);
+                *self.scores.entry(*player).or_insert(0) += round_points;
+                points.push((*player, round_points));
+            }
+            events.push(Event::RoundScored { points });
+        }
+
+        self.phase = self.round_phase();
+        Ok(events)
+    }
+
+    /// Takes back the last move of the round. <br>
+    /// `WizardError::NothingToTakeBack` is returned if there is no move or the last move isn't the player's.
+    fn take_back_move(&mut self, player: PlayerId) -> Result<Vec<Event>, WizardError> {
+        if !self.undo {
+            return Err(WizardError::TakeBackDisabled);
+        }
+
+        let round = self.round.as_mut().unwrap();
+        if round.history.last().map(|e| e.player) != Some(player) {
+            return Err(WizardError::NothingToTakeBack);
+        }
+        let scored = round.is_finished();
+        if scored {
+            for player in &round.seats {
+                *self.scores.entry(*player).or_insert(0) -= round.points(*player);
+            }
+        }
+        let step = round.undo().ok_or(WizardError::NothingToTakeBack)?;
+
+        // the move itself, the won trick and the scores of the round, the chat in between stays
+        let mut count = 1 + step.previous_leader.is_some() as usize + scored as usize;
+        let mut index = self.record.events.len();
+        while count > 0 {
+            index -= 1;
+            if !matches!(
+                self.record.events[index],
+                Event::Chat { .. } | Event::Emote { .. }
+            ) {
+                self.record.events.remove(index);
+                count -= 1;
+            }
+        }
+
+        self.phase = self.round_phase();
+        Ok(Vec::new())
+    }
+
+    fn next_round(&mut self) -> Vec<Event> {
+        self.round = None;
+        self.phase = if self.round_number as usize == self.rounds.len() {
+            Phase::Finished
+        } else {
+            Phase::Dealing
+        };
+        Vec::new()
+    }
+
+    /// Returns the phase of the current round.
+    fn round_phase(&self) -> Phase {
+        let round = self.round.as_ref().unwrap();
+        match round.next_player() {
+            Some(next_player) if round.is_bidding() => Phase::Bidding { next_player },
+            Some(next_player) => Phase::Playing {
+                trick: round.played.len() / round.player_count(),
+                next_player,
+            },
+            None => Phase::RoundScored,
+        }
+    }
+
+    /// Plays a whole game, which are all `rounds` of the schedule. <br>
+    /// The game stops with `WizardError::InputCancelled` if a human cancels a prompt.
+    pub fn play(&mut self) -> Result<(), WizardError> {
+        while self.phase != Phase::Finished {
+            self.play_round()?;
+        }
+        Ok(())
+    }
+
+    /// Plays the next round in the terminal: the cards are dealt, every player guesses the stitches
+    /// and all tricks are played. Computer controlled players act on their own, humans are asked. <br>
+    /// The player left of the dealer guesses first and leads the first trick.
+    pub fn play_round(&mut self) -> Result<(), WizardError> {
+        if self.phase == Phase::Setup {
+            self.apply(Action::Start)?;
+        }
+        if self.phase == Phase::RoundScored {
+            self.apply(Action::NextRound)?;
+        }
+        if self.phase == Phase::Finished {
+            return Ok(());
+        }
+
+        println!(
+            "{}",
+            i18n::text_with(
+                "game.round",
+                &[
+                    &(self.round_number + 1),
+                    &self.rounds.len(),
+                    &self.rounds[self.round_number as usize]
+                ]
+            )
+        );
+        let events = self.apply(Action::Deal)?;
+        self.print_events(&events);
+
+        while let Some((id, action)) = self.next_action()? {
+            let events = self.apply(action)?;
+            self.print_events(&events);
+
+            let description = match action {
+                Action::Guess(stitches) => i18n::text_with("game.guess_of", &[&stitches]),
+                Action::Play(card) => card.name(),
+                _ => continue,
+            };
+            let player = self.player(id)?;
+            if self.undo && player.bot.is_none() && self.take_back(player, &description)? {
+                println!(
+                    "{}",
+                    i18n::text_with("game.takes_back", &[&player.name, &description])
+                );
+                self.apply(Action::TakeBack(id))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the player whose turn it is together with their action. Computer controlled players act on their own,
+    /// humans are asked in the terminal. `None` is returned if it's nobody's turn.
+    pub fn next_action(&self) -> Result<Option<(PlayerId, Action)>, WizardError> {
+        Ok(Some(match self.phase {
+            Phase::TrumpSelection => {
+                // the dealer chooses the main color
+                let dealer = &self.players[self.dealer];
+                let hand = &self.round.as_ref().unwrap().hands[&dealer.id];
+                let color = match dealer.bot {
+                    Some(difficulty) => difficulty.choose_color(hand),
+                    None => dealer.choose_color()?,
+                };
+                (dealer.id, Action::ChooseColor(color))
+            }
+            Phase::Bidding { next_player } => (next_player, self.next_guess(next_player)?),
+            Phase::Playing { next_player, .. } => (next_player, self.next_card(next_player)?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Returns the player whose turn it is, which is the dealer while the main color is chosen.
+    pub fn next_player(&self) -> Option<PlayerId> {
+        match self.phase {
+            Phase::TrumpSelection => Some(self.players[self.dealer].id),
+            Phase::Bidding { next_player } | Phase::Playing { next_player, .. } => {
+                Some(next_player)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the guesses, cards or colors the player whose turn it is can choose from.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let Some(round) = &self.round else {
+            return Vec::new();
+        };
+        match self.phase {
+            Phase::TrumpSelection => CardColor::all_colors()
+                .into_iter()
+                .map(Action::ChooseColor)
+                .collect(),
+            Phase::Bidding { .. } => round
+                .stitch_options()
+                .into_iter()
+                .map(Action::Guess)
+                .collect(),
+            Phase::Playing { .. } => round
+                .playable_cards()
+                .into_iter()
+                .map(Action::Play)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the view of the given player on the cards of the current round.
+    fn counter(&self, id: PlayerId) -> CardCounter {
+        let round = self.round.as_ref().unwrap();
+        CardCounter {
+            hand: round.hands[&id].clone(),
+            played: round.played.clone(),
+            trick: round.trick.clone(),
+            trump_card: round.trump_card,
+            main_color: round.main_color,
+            player_count: self.players.len() as u32,
+        }
+    }
+
+    /// Lets the given bot or human guess the stitches.
+    fn next_guess(&self, id: PlayerId) -> Result<Action, WizardError> {
+        let player = self.player(id)?;
+        let options = self.round.as_ref().unwrap().stitch_options();
+        let counter = self.counter(id);
+
+        Ok(Action::Guess(match player.bot {
+            Some(difficulty) => difficulty.guess_stitches(&counter, &options),
+            None => {
+                println!(
+                    "{}",
+                    i18n::text_with(
+                        "event.cards",
+                        &[&player.name, &Game::card_names(&counter.hand)]
+                    )
+                );
+                if player.advisor {
+                    let advice = BidAdvice::new(&counter, &options);
+                    println!(
+                        "{}",
+                        i18n::text_with("game.advisor_guess", &[&advice.bid, &advice.rationale])
+                    );
+                }
+                player.guess_stitches(options)?
+            }
+        }))
+    }
+
+    /// Lets the given bot or human play a card.
+    fn next_card(&self, id: PlayerId) -> Result<Action, WizardError> {
+        let player = self.player(id)?;
+        let round = self.round.as_ref().unwrap();
+        let options = round.playable_cards();
+        let counter = self.counter(id);
+        let guess_stitches = round.guesses.get(&id).copied().unwrap_or(0);
+        let actual_stitches = round.stitches[&id];
+
+        Ok(Action::Play(match player.bot {
+            Some(difficulty) => {
+                difficulty.play_card(&counter, &options, guess_stitches, actual_stitches)
+            }
+            None => {
+                println!(
+                    "{}",
+                    i18n::text_with("game.trick_so_far", &[&Game::card_names(&round.trick)])
+                );
+                println!(
+                    "{}",
+                    i18n::text_with(
+                        "event.cards",
+                        &[&player.name, &Game::card_names(&counter.hand)]
+                    )
+                );
+
+                let mut recommended = None;
+                if player.advisor {
+                    let advice =
+                        PlayAdvice::new(&counter, &options, guess_stitches, actual_stitches);
+                    println!(
+                        "{}",
+                        i18n::text_with("game.advisor", &[&advice.rationale()])
+                    );
+                    recommended = Some(advice.card);
+                }
+                player.play_card(options, recommended)?
+            }
+        }))
+    }
+
+    /// Prints what happened in the terminal.
+    pub fn print_events(&self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::RoundStarted {
+                    trump_card,
+                    main_color,
+                    ..
+                } => println!("{}", Event::trump_text(trump_card, main_color)),
+                Event::Guessed { player, stitches } => println!(
+                    "{}",
+                    i18n::text_with("event.guessed", &[&self.name(*player), stitches])
+                ),
+                Event::Played { player, card } => println!(
+                    "{}",
+                    i18n::text_with("event.played", &[&self.name(*player), &card.name()])
+                ),
+                Event::TrickWon { player } => {
+                    let played = &self.round.as_ref().unwrap().played;
+                    let trick = &played[played.len() - self.players.len()..];
+                    println!(
+                        "{}",
+                        i18n::text_with(
+                            "game.trick_won",
+                            &[&self.name(*player), &Game::card_names(trick)]
+                        )
+                    );
+                }
+                Event::RoundScored { points } => {
+                    let round = self.round.as_ref().unwrap();
+                    for (player, points) in points {
+                        println!(
+                            "{}",
+                            i18n::text_with(
+                                "game.round_scored",
+                                &[
+                                    &self.name(*player),
+                                    &round.guesses.get(player).copied().unwrap_or(0),
+                                    &round.stitches[player],
+                                    &format!("{:+}", points),
+                                    &self.score(*player)
+                                ]
+                            )
+                        );
+                    }
+                }
+                Event::Chat { player, text } => println!(
+                    "{}",
+                    i18n::text_with("event.says", &[&self.name(*player), text])
+                ),
+                Event::Emote { player, emote } => println!(
+                    "{}",
+                    i18n::text_with("event.says", &[&self.name(*player), &emote.text()])
+                ),
+                Event::GameStarted { .. } => {}
+            }
+        }
+    }
+
+    /// Asks the given player whether they want to take back the described move and, if so, all other humans at the table
+    /// whether they allow it. Computer controlled players always allow it.
+    fn take_back(&self, player: &Player, action: &str) -> Result<bool, WizardError> {
+        if !player.take_back(action)? {
+            return Ok(false);
+        }
+        for other in &self.players {
+            if other.id != player.id
+                && other.bot.is_none()
+                && !other.allow_take_back(&player.name, action)?
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the name of the given player, which is `"Player N"` for players that aren't seated.
+    fn name(&self, id: PlayerId) -> String {
+        self.player(id)
+            .map(|e| e.name.clone())
+            .unwrap_or_else(|_| i18n::text_with("player.unknown", &[&id.0]))
+    }
+
+    fn card_names(cards: &[Card]) -> String {
+        if cards.is_empty() {
+            return "-".to_string();
+        }
+        cards
+            .iter()
+            .map(|card| card.name())
+            .collect::<Vec<String>>()
+            .join(", ")
     }
 }
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// The environment variable that selects the language, e.g. `WIZARD_LANG=de`.
+pub const ENV_VARIABLE: &str = "WIZARD_LANG";
+
+/// A language all user-facing text is available in. Every language has a message catalog in `src/wizard/locales`
+/// that maps the keys of the texts to their translation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub fn all_languages() -> Vec<Language> {
+        vec![Language::English, Language::German]
+    }
+
+    /// Returns the code of the language, `"en"` or `"de"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Returns the language of the given code, ignoring case and a region or encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::i18n::Language;
+    /// assert_eq!(Language::from_code("de"), Some(Language::German));
+    /// assert_eq!(Language::from_code("de_AT.UTF-8"), Some(Language::German));
+    /// assert_eq!(Language::from_code("EN"), Some(Language::English));
+    /// assert_eq!(Language::from_code("fr"), None);
+    /// ```
+    pub fn from_code(code: &str) -> Option<Language> {
+        let code = code
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        Language::all_languages()
+            .into_iter()
+            .find(|e| e.code() == code)
+    }
+
+    /// Returns the language of the environment variable `ENV_VARIABLE`, `None` if it isn't set to a known language.
+    pub fn from_env() -> Option<Language> {
+        std::env::var(ENV_VARIABLE)
+            .ok()
+            .and_then(|e| Language::from_code(&e))
+    }
+
+    /// Returns the message catalog of the language.
+    pub fn catalog(&self) -> &'static BTreeMap<String, String> {
+        static CATALOGS: OnceLock<Vec<BTreeMap<String, String>>> = OnceLock::new();
+        let catalogs = CATALOGS.get_or_init(|| {
+            [
+                include_str!("locales/en.json"),
+                include_str!("locales/de.json"),
+            ]
+            .iter()
+            .map(|e| serde_json::from_str(e).expect("The message catalogs are valid JSON."))
+            .collect()
+        });
+        &catalogs[*self as usize]
+    }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+/// Sets the language of all text from now on, for every thread.
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// Returns the language all text is shown in, English unless `set_language` was called.
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::German,
+        _ => Language::English,
+    }
+}
+
+/// Returns the text of the key in the current language.
+pub fn text(key: &str) -> String {
+    text_in(language(), key, &[])
+}
+
+/// Returns the text of the key in the current language with the placeholders `{0}`, `{1}`, ... replaced by
+/// the arguments.
+pub fn text_with(key: &str, args: &[&dyn Display]) -> String {
+    text_in(language(), key, args)
+}
+
+/// Returns the text of the key in the given language with the placeholders `{0}`, `{1}`, ... replaced by
+/// the arguments. Keys that are missing in the language are taken from English, unknown keys are returned as they are.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::i18n::{self, Language};
+/// assert_eq!(i18n::text_in(Language::German, "card.wizard", &[]), "Zauberer");
+/// assert_eq!(i18n::text_in(Language::English, "event.guessed", &[&"Max", &2]), "Max guesses 2.");
+/// assert_eq!(i18n::text_in(Language::German, "event.guessed", &[&"Max", &2]), "Max tippt 2.");
+/// assert_eq!(i18n::text_in(Language::German, "no.such.key", &[]), "no.such.key");
+/// ```
+pub fn text_in(language: Language, key: &str, args: &[&dyn Display]) -> String {
+    let template = language
+        .catalog()
+        .get(key)
+        .or_else(|| Language::English.catalog().get(key))
+        .map_or(key, |e| e.as_str());
+    let mut out = template.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        out = out.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    out
+}
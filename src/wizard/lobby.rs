@@ -0,0 +1,577 @@
+use crate::wizard::bot::Difficulty;
+use crate::wizard::error::WizardError;
+use crate::wizard::game::Game;
+use crate::wizard::i18n;
+use crate::wizard::net::{Client, ClientMessage, Connection, Server, ServerMessage};
+use crate::wizard::player::{Player, PlayerId};
+use crate::wizard::profile::ProfileStore;
+use crate::wizard::rating::INITIAL_RATING;
+use crate::wizard::rules::RuleSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long the lobby waits before it looks for new requests again.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum RoomStatus {
+    /// Players join and bots are added until the game is started.
+    Waiting,
+    Playing,
+    /// The final scores of every player.
+    Finished {
+        scores: Vec<(String, i32)>,
+    },
+    /// The game ended with the given error.
+    Aborted {
+        reason: String,
+    },
+}
+
+/// What clients of a lobby are told about a room.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: u32,
+    pub name: String,
+    pub rules: RuleSet,
+    pub seats: usize,
+    /// The names of the players in seating order.
+    pub players: Vec<String>,
+    /// The ratings of the players in the profile store, in the same order. Players without a profile have the
+    /// initial rating.
+    pub ratings: Vec<f64>,
+    pub status: RoomStatus,
+}
+
+/// A table in a lobby with its own game.
+pub struct Room {
+    pub info: RoomInfo,
+    /// The game with the remote players who joined, until it's started.
+    server: Option<Server>,
+    /// The session tokens of the remote players, to find the room of a player who rejoins.
+    tokens: BTreeSet<String>,
+    /// The spectators and returning players of the game, shared with its server.
+    spectators: Arc<Mutex<Vec<Connection>>>,
+    returning: Arc<Mutex<Vec<(String, Connection)>>>,
+}
+
+/// A server with many rooms that are played at the same time. Clients list the rooms, open new ones with the rules
+/// and amount of seats they want, take a seat or watch, add bots and start the game once every seat is taken. <br>
+/// Every room is played in its own thread. The results are added to the profile store, together with the name of
+/// the room.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::net::TcpListener;
+/// # use wizard::wizard::lobby::Lobby;
+/// # use wizard::wizard::net;
+/// # use wizard::wizard::profile::ProfileStore;
+/// let listener = TcpListener::bind("0.0.0.0:7879")?;
+/// let mut lobby = Lobby::new(ProfileStore::default(), ProfileStore::default_path());
+/// lobby.serve(move || net::accept(&listener));
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+pub struct Lobby {
+    pub rooms: Arc<Mutex<BTreeMap<u32, Room>>>,
+    pub store: Arc<Mutex<ProfileStore>>,
+    /// Where the store is saved after every game, `None` to keep it in memory only.
+    pub store_path: Option<PathBuf>,
+    /// The connections of the clients who are in the lobby but not in a room.
+    clients: Vec<Connection>,
+    incoming: Arc<Mutex<Vec<Connection>>>,
+}
+
+impl Lobby {
+    pub fn new(store: ProfileStore, store_path: Option<PathBuf>) -> Self {
+        Lobby {
+            rooms: Arc::default(),
+            store: Arc::new(Mutex::new(store)),
+            store_path,
+            clients: Vec::new(),
+            incoming: Arc::default(),
+        }
+    }
+
+    /// Runs the lobby forever. Every connection returned by `accept` enters the lobby.
+    pub fn serve(
+        &mut self,
+        mut accept: impl FnMut() -> Result<(Connection, String), WizardError> + Send + 'static,
+    ) {
+        let incoming = Arc::clone(&self.incoming);
+        thread::spawn(move || {
+            while let Ok((connection, address)) = accept() {
+                println!("{}", i18n::text_with("lobby.entered", &[&address]));
+                incoming.lock().unwrap().push(connection);
+            }
+        });
+
+        loop {
+            self.poll();
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Returns what clients are told about the rooms.
+    pub fn room_infos(&self) -> Vec<RoomInfo> {
+        let rooms = self.rooms.lock().unwrap();
+        rooms.values().map(|e| e.info.clone()).collect()
+    }
+
+    /// Answers every request that arrived since the last call, without waiting for new ones.
+    pub fn poll(&mut self) {
+        self.clients.append(&mut self.incoming.lock().unwrap());
+
+        for mut connection in std::mem::take(&mut self.clients) {
+            match connection.try_receive::<ClientMessage>() {
+                Ok(None) => self.clients.push(connection),
+                Ok(Some(message)) => {
+                    if let Some(connection) = self.request(connection, message) {
+                        self.clients.push(connection);
+                    }
+                }
+                // the client left
+                Err(_) => {}
+            }
+        }
+
+        // players who sit in a room may add bots and start the game
+        let mut requests = Vec::new();
+        let mut left = Vec::new();
+        for (id, room) in self.rooms.lock().unwrap().iter_mut() {
+            let Some(server) = &mut room.server else {
+                continue;
+            };
+            for (player, connection) in &mut server.connections {
+                match connection.try_receive::<ClientMessage>() {
+                    Ok(None) => {}
+                    Ok(Some(message)) => requests.push((*id, *player, message)),
+                    Err(_) => left.push((*id, *player)),
+                }
+            }
+        }
+        for (room, player) in left {
+            self.leave(room, player);
+        }
+        for (room, player, message) in requests {
+            let reply = match message {
+                ClientMessage::ListRooms
+                | ClientMessage::CreateRoom { .. }
+                | ClientMessage::AddBot { .. }
+                | ClientMessage::StartGame { .. } => self.reply(message, Some((room, player))),
+                ClientMessage::Chat { .. } | ClientMessage::Emote { .. } => {
+                    Some(ServerMessage::Rejected {
+                        reason: i18n::text("lobby.chat_closed"),
+                    })
+                }
+                _ => Some(ServerMessage::Rejected {
+                    reason: i18n::text("lobby.already_seated"),
+                }),
+            };
+            if let Some(reply) = reply {
+                self.send_seated(room, player, &reply);
+            }
+        }
+    }
+
+    /// Answers a request of a client in the lobby. The connection is returned unless the client took a seat, watches
+    /// or left.
+    fn request(
+        &mut self,
+        mut connection: Connection,
+        message: ClientMessage,
+    ) -> Option<Connection> {
+        let rejected = |reason: String| ServerMessage::Rejected { reason };
+        if let ClientMessage::JoinRoom { room, name } = message {
+            return self.join(connection, room, name);
+        }
+        let rooms = self.rooms.lock().unwrap();
+        let reply = match message {
+            ClientMessage::WatchRoom { room, name } => match rooms.get(&room) {
+                Some(room) => {
+                    println!(
+                        "{}",
+                        i18n::text_with("lobby.watches", &[&name, &room.info.name])
+                    );
+                    if connection.send(&ServerMessage::Watching).is_ok() {
+                        room.spectators.lock().unwrap().push(connection);
+                    }
+                    return None;
+                }
+                None => rejected(i18n::text("lobby.no_such_room")),
+            },
+            ClientMessage::Rejoin { token } => {
+                match rooms.values().find(|e| e.tokens.contains(&token)) {
+                    Some(room) => {
+                        room.returning.lock().unwrap().push((token, connection));
+                        return None;
+                    }
+                    None => rejected(i18n::text("net.no_session")),
+                }
+            }
+            ClientMessage::Join { .. }
+            | ClientMessage::Watch { .. }
+            | ClientMessage::Act { .. } => rejected(i18n::text("lobby.choose_room")),
+            message => {
+                drop(rooms);
+                match self.reply(message, None) {
+                    Some(reply) => reply,
+                    None => return Some(connection),
+                }
+            }
+        };
+        connection.send(&reply).ok()?;
+        Some(connection)
+    }
+
+    /// Seats a client in a waiting room. The client gets the session token with the welcome, and a number if the
+    /// name is taken. The rooms aren't locked while the welcome is sent. The connection is returned if the client
+    /// stays in the lobby.
+    fn join(&mut self, mut connection: Connection, room: u32, name: String) -> Option<Connection> {
+        let rooms = self.rooms.lock().unwrap();
+        let reason = match rooms.get(&room) {
+            Some(Room {
+                info,
+                server: Some(server),
+                ..
+            }) if info.players.len() < info.seats => {
+                let (player, token, welcome) = server.welcome(Player::with_name(name));
+                drop(rooms);
+                connection.send(&welcome).ok()?;
+
+                let mut rooms = self.rooms.lock().unwrap();
+                if let Some(Room {
+                    info,
+                    server: Some(server),
+                    tokens,
+                    ..
+                }) = rooms.get_mut(&room)
+                {
+                    let seat = server.game.players.len();
+                    server.take_seat(seat, player, token.clone(), connection);
+                    tokens.insert(token);
+                    (info.players, info.ratings) = seated(&self.store, &server.game.players);
+                }
+                return None;
+            }
+            Some(_) => i18n::text("lobby.no_free_seat"),
+            None => i18n::text("lobby.no_such_room"),
+        };
+        drop(rooms);
+        connection.send(&ServerMessage::Rejected { reason }).ok()?;
+        Some(connection)
+    }
+
+    /// Answers a request that doesn't need the connection of the client. `seated` is the room and player of a client
+    /// who sits in a room. `None` is returned if the reply was sent already.
+    fn reply(
+        &mut self,
+        message: ClientMessage,
+        seated: Option<(u32, PlayerId)>,
+    ) -> Option<ServerMessage> {
+        let result = match message {
+            ClientMessage::ListRooms => Ok(()),
+            ClientMessage::CreateRoom { name, rules, seats } => self.create(name, rules, seats),
+            ClientMessage::AddBot { room, difficulty } => self.add_bot(room, difficulty),
+            ClientMessage::StartGame { room } => {
+                // the reply has to be sent before the game starts sending
+                let rooms = self.room_infos();
+                if let Some((seated_room, player)) = seated {
+                    if seated_room == room && self.ready(room).is_ok() {
+                        self.send_seated(room, player, &ServerMessage::Rooms { rooms });
+                        return self.start(room).err().map(|e| ServerMessage::Rejected {
+                            reason: e.to_string(),
+                        });
+                    }
+                }
+                self.start(room)
+            }
+            _ => Ok(()),
+        };
+        Some(match result {
+            Ok(()) => ServerMessage::Rooms {
+                rooms: self.room_infos(),
+            },
+            Err(e) => ServerMessage::Rejected {
+                reason: e.to_string(),
+            },
+        })
+    }
+
+    /// Opens a new room. `WizardError::InvalidPlayerCount` is returned if there are not 3 to 6 seats,
+    /// an error of the schedule if the rules can't be played with that many players.
+    pub fn create(
+        &mut self,
+        name: String,
+        rules: RuleSet,
+        seats: usize,
+    ) -> Result<(), WizardError> {
+        if !(3..=6).contains(&seats) {
+            return Err(WizardError::InvalidPlayerCount(seats));
+        }
+        rules.schedule.rounds(seats, &mut rand::thread_rng())?;
+
+        let mut rooms = self.rooms.lock().unwrap();
+        let id = rooms.keys().last().map_or(1, |e| e + 1);
+        let server = Server::new(Game {
+            rules: rules.clone(),
+            ..Game::default()
+        });
+        println!(
+            "{}",
+            i18n::text_with("lobby.opened", &[&name, &rules.description()])
+        );
+        rooms.insert(
+            id,
+            Room {
+                info: RoomInfo {
+                    id,
+                    name,
+                    rules,
+                    seats,
+                    players: Vec::new(),
+                    ratings: Vec::new(),
+                    status: RoomStatus::Waiting,
+                },
+                spectators: Arc::clone(&server.spectators),
+                returning: Arc::clone(&server.returning),
+                server: Some(server),
+                tokens: BTreeSet::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Seats a bot in a waiting room. Bots of the same difficulty are numbered.
+    pub fn add_bot(&mut self, room: u32, difficulty: Difficulty) -> Result<(), WizardError> {
+        let mut rooms = self.rooms.lock().unwrap();
+        let Some(Room {
+            info,
+            server: Some(server),
+            ..
+        }) = rooms.get_mut(&room)
+        else {
+            return Err(WizardError::Room(i18n::text("lobby.no_waiting_room")));
+        };
+        if info.players.len() >= info.seats {
+            return Err(WizardError::Room(i18n::text("lobby.no_free_seat")));
+        }
+
+        let number = server
+            .game
+            .players
+            .iter()
+            .filter(|e| e.bot == Some(difficulty))
+            .count();
+        let mut bot = Player::bot(difficulty, number as u32 + 1);
+        bot.name = Player::unique_name(&bot.name, &server.game.players);
+        server.game.players.push(bot);
+        (info.players, info.ratings) = seated(&self.store, &server.game.players);
+        Ok(())
+    }
+
+    /// Returns an error unless every seat of the waiting room is taken.
+    fn ready(&self, room: u32) -> Result<(), WizardError> {
+        let rooms = self.rooms.lock().unwrap();
+        match rooms.get(&room) {
+            Some(Room {
+                info,
+                server: Some(_),
+                ..
+            }) if info.players.len() == info.seats => Ok(()),
+            Some(Room {
+                info,
+                server: Some(_),
+                ..
+            }) => Err(WizardError::Room(i18n::text_with(
+                "lobby.free_seats",
+                &[&(info.seats - info.players.len())],
+            ))),
+            _ => Err(WizardError::Room(i18n::text("lobby.no_waiting_room"))),
+        }
+    }
+
+    /// Starts the game of a room in its own thread once every seat is taken. When the game is over, its result is
+    /// added to the store.
+    pub fn start(&mut self, room: u32) -> Result<(), WizardError> {
+        self.ready(room)?;
+        let mut server = {
+            let mut rooms = self.rooms.lock().unwrap();
+            // the room may have been closed since it was found ready
+            let Some((info, Some(server))) =
+                rooms.get_mut(&room).map(|e| (&mut e.info, e.server.take()))
+            else {
+                return Err(WizardError::Room(i18n::text("lobby.no_waiting_room")));
+            };
+            info.status = RoomStatus::Playing;
+            server
+        };
+
+        let rooms = Arc::clone(&self.rooms);
+        let store = Arc::clone(&self.store);
+        let store_path = self.store_path.clone();
+        thread::spawn(move || {
+            let status = match server.play() {
+                Ok(()) => {
+                    let mut rooms = rooms.lock().unwrap();
+                    let name = rooms.get(&room).map(|e| e.info.name.clone());
+                    let mut store = store.lock().unwrap();
+                    store.record_game(&server.game.record);
+                    if let Some(result) = store.games.last_mut() {
+                        result.room = name;
+                    }
+                    if let Some(path) = &store_path {
+                        if let Err(e) = store.save(path) {
+                            println!("{}", i18n::text_with("main.profiles_not_saved", &[&e]));
+                        }
+                    }
+                    drop(store);
+
+                    let scores = server
+                        .game
+                        .players
+                        .iter()
+                        .map(|e| (e.name.clone(), server.game.score(e.id)))
+                        .collect();
+                    if let Some(room) = rooms.get_mut(&room) {
+                        room.info.status = RoomStatus::Finished { scores };
+                    }
+                    return;
+                }
+                Err(e) => RoomStatus::Aborted {
+                    reason: e.to_string(),
+                },
+            };
+            if let Some(room) = rooms.lock().unwrap().get_mut(&room) {
+                room.info.status = status;
+            }
+        });
+        Ok(())
+    }
+
+    /// Frees the seat of a player who left a waiting room.
+    fn leave(&mut self, room: u32, player: PlayerId) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(Room {
+            info,
+            server: Some(server),
+            ..
+        }) = rooms.get_mut(&room)
+        {
+            server.unseat(player);
+            (info.players, info.ratings) = seated(&self.store, &server.game.players);
+        }
+    }
+
+    fn send_seated(&mut self, room: u32, player: PlayerId, message: &ServerMessage) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(Room {
+            server: Some(server),
+            ..
+        }) = rooms.get_mut(&room)
+        {
+            if let Some(connection) = server.connections.get_mut(&player) {
+                let _ = connection.send(message);
+            }
+        }
+    }
+}
+
+/// A client in a lobby that isn't seated yet.
+pub struct LobbyClient {
+    pub connection: Connection,
+}
+
+impl LobbyClient {
+    /// Enters the lobby at the given address.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, WizardError> {
+        Ok(LobbyClient {
+            connection: Connection::new(TcpStream::connect(address)?)?,
+        })
+    }
+
+    pub fn rooms(&mut self) -> Result<Vec<RoomInfo>, WizardError> {
+        request(&mut self.connection, ClientMessage::ListRooms)
+    }
+
+    /// Opens a room and returns the rooms of the lobby.
+    pub fn create(
+        &mut self,
+        name: &str,
+        rules: RuleSet,
+        seats: usize,
+    ) -> Result<Vec<RoomInfo>, WizardError> {
+        let name = name.to_string();
+        request(
+            &mut self.connection,
+            ClientMessage::CreateRoom { name, rules, seats },
+        )
+    }
+
+    pub fn add_bot(
+        &mut self,
+        room: u32,
+        difficulty: Difficulty,
+    ) -> Result<Vec<RoomInfo>, WizardError> {
+        request(
+            &mut self.connection,
+            ClientMessage::AddBot { room, difficulty },
+        )
+    }
+
+    pub fn start(&mut self, room: u32) -> Result<Vec<RoomInfo>, WizardError> {
+        request(&mut self.connection, ClientMessage::StartGame { room })
+    }
+
+    /// Takes a seat in the given room. The returned client can still add bots and start the game with `request`.
+    pub fn join(mut self, room: u32, name: &str) -> Result<Client, WizardError> {
+        let name = name.to_string();
+        self.connection
+            .send(&ClientMessage::JoinRoom { room, name })?;
+        Client::welcome(self.connection)
+    }
+
+    /// Watches the game of the given room.
+    pub fn watch(mut self, room: u32, name: &str) -> Result<Client, WizardError> {
+        let name = name.to_string();
+        self.connection
+            .send(&ClientMessage::WatchRoom { room, name })?;
+        Client::watching(self.connection)
+    }
+}
+
+/// Sends a lobby request and returns the rooms of the answer. <br>
+/// `WizardError::Room` is returned with the reason if the lobby rejected the request.
+pub fn request(
+    connection: &mut Connection,
+    message: ClientMessage,
+) -> Result<Vec<RoomInfo>, WizardError> {
+    connection.send(&message)?;
+    match connection.receive()? {
+        ServerMessage::Rooms { rooms } => Ok(rooms),
+        ServerMessage::Rejected { reason } => Err(WizardError::Room(reason)),
+        message => Err(WizardError::Network(i18n::text_with(
+            "net.unexpected",
+            &[&format!("{:?}", message)],
+        ))),
+    }
+}
+
+/// Returns the names of the players and their ratings in the store.
+fn seated(store: &Mutex<ProfileStore>, players: &[Player]) -> (Vec<String>, Vec<f64>) {
+    let store = store.lock().unwrap();
+    players
+        .iter()
+        .map(|player| {
+            let rating = store
+                .player_profile(player)
+                .map_or(INITIAL_RATING, |e| e.rating);
+            (player.name.clone(), rating)
+        })
+        .unzip()
+}
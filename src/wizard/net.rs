@@ -0,0 +1,913 @@
+use crate::wizard::bot::Difficulty;
+use crate::wizard::card::Card;
+use crate::wizard::chat::{self, Emote, RateLimit};
+use crate::wizard::error::WizardError;
+use crate::wizard::game::{Action, Game, Phase};
+use crate::wizard::i18n;
+use crate::wizard::lobby::RoomInfo;
+use crate::wizard::player::{Player, PlayerId};
+use crate::wizard::record::Event;
+use crate::wizard::rules::RuleSet;
+use crate::wizard::view::View;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A message from a client to the server.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// The first message of every client that wants to play.
+    Join {
+        name: String,
+    },
+    /// The first message of every client that only wants to watch.
+    Watch {
+        name: String,
+    },
+    /// The first message of a player who lost the connection, with the session token of `ServerMessage::Welcome`.
+    Rejoin {
+        token: String,
+    },
+    /// The answer to `ServerMessage::Turn`.
+    Act {
+        action: Action,
+    },
+    /// Says something at the table. Players can chat at any time, it's passed on to everyone as `Event::Chat`.
+    Chat {
+        text: String,
+    },
+    Emote {
+        emote: Emote,
+    },
+    /// Asks a lobby for its rooms. Like every lobby request it's answered with `ServerMessage::Rooms`.
+    ListRooms,
+    /// Opens a room in a lobby for a game with the given rules and amount of seats.
+    CreateRoom {
+        name: String,
+        rules: RuleSet,
+        seats: usize,
+    },
+    /// Takes a seat in a room of a lobby, answered with `ServerMessage::Welcome`.
+    JoinRoom {
+        room: u32,
+        name: String,
+    },
+    /// Watches the game of a room in a lobby, answered with `ServerMessage::Watching`.
+    WatchRoom {
+        room: u32,
+        name: String,
+    },
+    /// Seats a bot in a room of a lobby.
+    AddBot {
+        room: u32,
+        difficulty: Difficulty,
+    },
+    /// Starts the game of a room once every seat is taken.
+    StartGame {
+        room: u32,
+    },
+}
+
+impl From<Action> for ClientMessage {
+    fn from(action: Action) -> Self {
+        ClientMessage::Act { action }
+    }
+}
+
+/// A message from the server to a client.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The client took a seat as the given player. The token takes the seat again after losing the connection.
+    Welcome { player: PlayerId, token: String },
+    /// The client watches the game as a spectator.
+    Watching,
+    /// Something happened in the game. The hands of the other players are removed.
+    Events { events: Vec<Event> },
+    /// The table after something happened, as the client may see it.
+    State { view: Box<View> },
+    /// It's the turn of the client, it has to answer with one of the options of the view.
+    Turn { view: Box<View> },
+    /// The client took too long and the given action was chosen for it. If a bot took over the seat, the connection
+    /// is closed and the client has to rejoin to play again.
+    TimedOut { action: Action },
+    /// The action of the client broke the rules, it gets another turn.
+    Rejected { reason: String },
+    /// The hands every player got at the start of the given round. Spectators are sent them once the round is
+    /// old enough, see `Server::reveal_hands`.
+    Hands {
+        round: u32,
+        hands: Vec<(PlayerId, Vec<Card>)>,
+    },
+    /// The rooms of a lobby.
+    Rooms { rooms: Vec<RoomInfo> },
+    /// The final scores of every player.
+    GameOver { scores: Vec<(String, i32)> },
+}
+
+/// How the messages of a connection are sent.
+enum Transport {
+    /// One JSON message per line over TCP, used by the terminal client.
+    Lines {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    },
+    /// One JSON message per text frame, used by the browser front end.
+    #[cfg(feature = "web")]
+    WebSocket(Box<tungstenite::WebSocket<TcpStream>>),
+}
+
+/// A connection that sends and receives messages as JSON, one message per line or per WebSocket frame.
+pub struct Connection {
+    transport: Transport,
+    /// The start of a line that was only partly received by `try_receive`.
+    pending: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, WizardError> {
+        Ok(Connection {
+            transport: Transport::Lines {
+                reader: BufReader::new(stream.try_clone()?),
+                writer: stream,
+            },
+            pending: Vec::new(),
+        })
+    }
+
+    /// Returns a connection over an accepted WebSocket.
+    #[cfg(feature = "web")]
+    pub fn web_socket(socket: tungstenite::WebSocket<TcpStream>) -> Self {
+        Connection {
+            transport: Transport::WebSocket(Box::new(socket)),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), WizardError> {
+        let line =
+            serde_json::to_string(message).map_err(|e| WizardError::Network(e.to_string()))?;
+        match &mut self.transport {
+            Transport::Lines { writer, .. } => {
+                writer.write_all(line.as_bytes())?;
+                writer.write_all(b"\n")?;
+            }
+            #[cfg(feature = "web")]
+            Transport::WebSocket(socket) => socket
+                .send(tungstenite::Message::text(line))
+                .map_err(|e| WizardError::Network(e.to_string()))?,
+        }
+        Ok(())
+    }
+
+    /// Waits for the next message. `WizardError::Network` is returned if the connection was closed or the message can't be read.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Result<T, WizardError> {
+        loop {
+            if let Some(message) = self.read()? {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Returns the next message if one was received already, without waiting for one.
+    pub fn try_receive<T: DeserializeOwned>(&mut self) -> Result<Option<T>, WizardError> {
+        self.set_nonblocking(true)?;
+        let message = self.read();
+        self.set_nonblocking(false)?;
+        message
+    }
+
+    /// Waits at most the given time for the next message, `None` if none came.
+    pub fn receive_timeout<T: DeserializeOwned>(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<T>, WizardError> {
+        self.set_read_timeout(Some(timeout))?;
+        let message = self.read();
+        self.set_read_timeout(None)?;
+        message
+    }
+
+    /// Reads the next message, `None` if a nonblocking connection has none yet or the read timed out.
+    fn read<T: DeserializeOwned>(&mut self) -> Result<Option<T>, WizardError> {
+        let closed = || WizardError::Network(i18n::text("net.closed"));
+        let text = match &mut self.transport {
+            Transport::Lines { reader, .. } => match reader.read_until(b'\n', &mut self.pending) {
+                Ok(0) => return Err(closed()),
+                Ok(_) if self.pending.ends_with(b"\n") => {
+                    String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned()
+                }
+                Ok(_) => return Ok(None),
+                Err(e) if waiting(&e) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            },
+            #[cfg(feature = "web")]
+            Transport::WebSocket(socket) => match socket.read() {
+                Ok(tungstenite::Message::Text(text)) => text,
+                Ok(tungstenite::Message::Close(_)) => return Err(closed()),
+                // pings are answered by tungstenite
+                Ok(_) => return Ok(None),
+                Err(tungstenite::Error::Io(e)) if waiting(&e) => return Ok(None),
+                Err(e) => return Err(WizardError::Network(e.to_string())),
+            },
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| WizardError::Network(e.to_string()))
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), WizardError> {
+        match &mut self.transport {
+            Transport::Lines { writer, .. } => writer.set_read_timeout(timeout)?,
+            #[cfg(feature = "web")]
+            Transport::WebSocket(socket) => socket.get_mut().set_read_timeout(timeout)?,
+        }
+        Ok(())
+    }
+
+    fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), WizardError> {
+        match &mut self.transport {
+            Transport::Lines { writer, .. } => writer.set_nonblocking(nonblocking)?,
+            #[cfg(feature = "web")]
+            Transport::WebSocket(socket) => socket.get_mut().set_nonblocking(nonblocking)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns if the error only means that no message came yet.
+fn waiting(error: &io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// Returns if an error of `TcpListener::accept` only concerns the client that connected, not the listener.
+pub fn connection_failed(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset | ErrorKind::Interrupted
+    )
+}
+
+/// Waits for the next client on the listener and returns its connection and address. Clients whose connection
+/// fails before it's set up are skipped. <br>
+/// `WizardError::Network` is returned if the listener fails.
+pub fn accept(listener: &TcpListener) -> Result<(Connection, String), WizardError> {
+    loop {
+        let (stream, address) = match listener.accept() {
+            Ok(client) => client,
+            Err(e) if connection_failed(&e) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if let Ok(connection) = Connection::new(stream) {
+            return Ok((connection, address.to_string()));
+        }
+    }
+}
+
+/// What the server does when a remote player doesn't act in time.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TimeoutPolicy {
+    /// The first legal guess, card or color is chosen for the player.
+    AutoPlay,
+    /// A bot of the given difficulty takes the seat until the player rejoins.
+    Bot(Difficulty),
+}
+
+/// A game hosted for players who join over the network. The other players sit at the host, bots act on their own
+/// and humans are asked in the terminal of the host. <br>
+/// Every remote player gets a session token with which they take their seat again after losing the connection.
+pub struct Server {
+    pub game: Game,
+    /// The connections of the players who joined over the network.
+    pub connections: BTreeMap<PlayerId, Connection>,
+    /// The connections of the clients who watch. They are sent every event without the hands of the players and
+    /// can't act.
+    pub spectators: Arc<Mutex<Vec<Connection>>>,
+    /// After how many rounds spectators are shown the hands a round started with, `None` for never.
+    /// With 0 the hands are shown as soon as the round is scored.
+    pub reveal_hands: Option<u32>,
+    /// How long a remote player has for every action, `None` for no limit. Players without a connection are waited
+    /// for just as long.
+    pub timeout: Option<Duration>,
+    pub on_timeout: TimeoutPolicy,
+    /// How many chat messages and emotes every player may send.
+    pub chat_limit: RateLimit,
+    /// The rounds whose hands were shown to the spectators.
+    revealed: u32,
+    /// The session tokens of the remote players.
+    tokens: BTreeMap<String, PlayerId>,
+    /// The connections of clients who want to take their seat again, with their session tokens. They are seated
+    /// while the game is played.
+    pub returning: Arc<Mutex<Vec<(String, Connection)>>>,
+    /// The seats a bot plays for until their player returns.
+    away: BTreeSet<PlayerId>,
+}
+
+/// How long the server waits for a remote player before it looks for returning clients again.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl Server {
+    /// Waits for a client for every seat in `remote_seats` and seats the joining players there, in ascending order.
+    /// The players of the game take the remaining seats.
+    pub fn host(
+        listener: &TcpListener,
+        game: Game,
+        remote_seats: &[usize],
+    ) -> Result<Self, WizardError> {
+        Server::with_clients(game, remote_seats, || accept(listener))
+    }
+
+    /// Like `host`, but the connections of the clients and their addresses are returned by `accept`.
+    /// Clients who only watch don't take a seat.
+    pub fn with_clients(
+        game: Game,
+        remote_seats: &[usize],
+        mut accept: impl FnMut() -> Result<(Connection, String), WizardError>,
+    ) -> Result<Self, WizardError> {
+        let mut seats = remote_seats.to_vec();
+        seats.sort();
+
+        let mut server = Server::new(game);
+        for seat in seats {
+            let (connection, address, name) = loop {
+                let (mut connection, address) = accept()?;
+                match connection.receive()? {
+                    ClientMessage::Join { name } => break (connection, address, name),
+                    ClientMessage::Watch { name } => {
+                        server.add_spectator(connection, &name, &address)?
+                    }
+                    // taken care of once the game is played
+                    ClientMessage::Rejoin { token } => {
+                        server.returning.lock().unwrap().push((token, connection))
+                    }
+                    _ => {
+                        return Err(WizardError::Network(i18n::text_with(
+                            "net.didnt_join",
+                            &[&address],
+                        )))
+                    }
+                }
+            };
+
+            let player = Player::with_name(name);
+            let id = player.id;
+            server.seat(seat, player, connection)?;
+            println!(
+                "{}",
+                i18n::text_with("net.joined", &[&server.game.player(id)?.name, &address])
+            );
+        }
+
+        Ok(server)
+    }
+
+    /// Returns a server for the game without any remote players yet.
+    pub fn new(game: Game) -> Self {
+        Server {
+            game,
+            connections: BTreeMap::new(),
+            spectators: Arc::default(),
+            reveal_hands: None,
+            timeout: None,
+            on_timeout: TimeoutPolicy::AutoPlay,
+            chat_limit: RateLimit::default(),
+            revealed: 0,
+            tokens: BTreeMap::new(),
+            returning: Arc::default(),
+            away: BTreeSet::new(),
+        }
+    }
+
+    /// Seats a remote player at the given seat, or at the end of the table, and sends them their session token,
+    /// which is returned. A player whose name is taken at the table gets a number, see `Player::unique_name`.
+    pub fn seat(
+        &mut self,
+        seat: usize,
+        player: Player,
+        mut connection: Connection,
+    ) -> Result<String, WizardError> {
+        let (player, token, welcome) = self.welcome(player);
+        connection.send(&welcome)?;
+        self.take_seat(seat, player, token.clone(), connection);
+        Ok(token)
+    }
+
+    /// Returns the player with a name that is unique at the table, a new session token and the welcome that tells
+    /// the player both. Nothing is sent, the player is seated with `Server::take_seat` once the welcome arrived.
+    pub fn welcome(&self, player: Player) -> (Player, String, ServerMessage) {
+        let player = Player {
+            name: Player::unique_name(&player.name, &self.game.players),
+            ..player
+        };
+        let token = format!("{:032x}", rand::random::<u128>());
+        let welcome = ServerMessage::Welcome {
+            player: player.id,
+            token: token.clone(),
+        };
+        (player, token, welcome)
+    }
+
+    /// Seats a player who got the welcome of `Server::welcome` at the given seat, or at the end of the table.
+    pub fn take_seat(
+        &mut self,
+        seat: usize,
+        player: Player,
+        token: String,
+        connection: Connection,
+    ) {
+        self.tokens.insert(token, player.id);
+        self.connections.insert(player.id, connection);
+        let seat = seat.min(self.game.players.len());
+        self.game.players.insert(seat, player);
+    }
+
+    /// Removes a remote player who left before the game started.
+    pub fn unseat(&mut self, id: PlayerId) {
+        self.connections.remove(&id);
+        self.tokens.retain(|_, e| *e != id);
+        self.game.players.retain(|e| e.id != id);
+    }
+
+    /// Lets clients watch and players who lost their connection return while the game is played. Every client
+    /// returned by `accept` either watches or rejoins, clients who want to join are told that the game is full.
+    /// `accept` skips clients whose connection fails, so an error of it means that the listener failed. The error
+    /// is shown and no more clients are admitted.
+    pub fn admit(
+        &self,
+        mut accept: impl FnMut() -> Result<(Connection, String), WizardError> + Send + 'static,
+    ) {
+        let spectators = Arc::clone(&self.spectators);
+        let returning = Arc::clone(&self.returning);
+        thread::spawn(move || loop {
+            let (mut connection, address) = match accept() {
+                Ok(client) => client,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    break;
+                }
+            };
+            match connection.receive() {
+                Ok(ClientMessage::Watch { name }) => {
+                    println!("{}", i18n::text_with("net.watches", &[&name, &address]));
+                    if connection.send(&ServerMessage::Watching).is_ok() {
+                        spectators.lock().unwrap().push(connection);
+                    }
+                }
+                Ok(ClientMessage::Rejoin { token }) => {
+                    returning.lock().unwrap().push((token, connection))
+                }
+                Ok(_) => {
+                    let reason = i18n::text("net.game_full");
+                    let _ = connection.send(&ServerMessage::Rejected { reason });
+                }
+                Err(_) => {}
+            }
+        });
+    }
+
+    /// Adds a client who watches and sends them the current state of the table.
+    fn add_spectator(
+        &mut self,
+        mut connection: Connection,
+        name: &str,
+        address: &str,
+    ) -> Result<(), WizardError> {
+        println!("{}", i18n::text_with("net.watches", &[&name, &address]));
+        connection.send(&ServerMessage::Watching)?;
+        connection.send(&ServerMessage::State {
+            view: Box::new(View::new(&self.game, None)),
+        })?;
+        self.spectators.lock().unwrap().push(connection);
+        Ok(())
+    }
+
+    /// Plays the whole game. Remote players are sent their view when it's their turn and every other client is sent
+    /// what happened, without the hands of the other players.
+    pub fn play(&mut self) -> Result<(), WizardError> {
+        loop {
+            self.seat_returning();
+            self.read_chat(None);
+            let action = match self.game.phase {
+                Phase::Finished => break,
+                Phase::Setup => Action::Start,
+                Phase::Dealing => Action::Deal,
+                Phase::RoundScored => Action::NextRound,
+                _ => {
+                    let id = self.game.next_player().unwrap();
+                    if self.is_remote(id) {
+                        self.remote_action(id)?
+                    } else {
+                        self.game.next_action()?.unwrap().1
+                    }
+                }
+            };
+
+            match self.game.apply(action) {
+                Ok(events) => {
+                    self.game.print_events(&events);
+                    self.broadcast(&events);
+                }
+                Err(e) => match self.game.next_player() {
+                    Some(id) if self.is_remote(id) => {
+                        let rejected = ServerMessage::Rejected {
+                            reason: e.to_string(),
+                        };
+                        self.send(id, &rejected);
+                    }
+                    _ => return Err(e),
+                },
+            }
+        }
+
+        let scores: Vec<(String, i32)> = self
+            .game
+            .players
+            .iter()
+            .map(|e| (e.name.clone(), self.game.score(e.id)))
+            .collect();
+        let game_over = ServerMessage::GameOver { scores };
+        for connection in self.connections.values_mut() {
+            // the game counts even if someone left before the end
+            let _ = connection.send(&game_over);
+        }
+
+        // the game is over, nothing has to be hidden anymore
+        let mut messages = Vec::new();
+        if self.reveal_hands.is_some() {
+            messages.extend(self.hands_until(self.game.round_number));
+        }
+        messages.push(game_over);
+        self.update_spectators(&messages);
+        Ok(())
+    }
+
+    /// Returns if the player joined over the network and no bot plays for them.
+    fn is_remote(&self, id: PlayerId) -> bool {
+        self.tokens.values().any(|e| *e == id) && !self.away.contains(&id)
+    }
+
+    /// Waits for the action of a remote player. Returning clients are seated meanwhile. If the player takes longer
+    /// than the timeout, the action is chosen as `on_timeout` says.
+    fn remote_action(&mut self, id: PlayerId) -> Result<Action, WizardError> {
+        let deadline = self.timeout.map(|e| Instant::now() + e);
+        let mut turn_sent = false;
+        loop {
+            if self.seat_returning().contains(&id) {
+                // the new connection doesn't know it's their turn
+                turn_sent = false;
+            }
+
+            match self.connections.get_mut(&id) {
+                Some(connection) if !turn_sent => {
+                    let view = Box::new(View::new(&self.game, Some(id)));
+                    turn_sent = connection.send(&ServerMessage::Turn { view }).is_ok();
+                    if !turn_sent {
+                        self.disconnect(id);
+                    }
+                }
+                Some(connection) => match connection.receive_timeout(POLL_INTERVAL) {
+                    // taking back needs the consent of the table, which remote players can't be asked for
+                    Ok(Some(ClientMessage::Act {
+                        action: Action::TakeBack(_),
+                    })) => {
+                        let reason = i18n::text("net.no_take_back");
+                        self.send(id, &ServerMessage::Rejected { reason });
+                        turn_sent = false;
+                    }
+                    Ok(Some(ClientMessage::Act { action })) => return Ok(action),
+                    Ok(Some(message)) => self.chat(id, message),
+                    Ok(None) => {}
+                    Err(_) => self.disconnect(id),
+                },
+                None => thread::sleep(POLL_INTERVAL),
+            }
+            self.read_chat(Some(id));
+
+            if deadline.is_some_and(|e| Instant::now() >= e) {
+                return self.time_out(id);
+            }
+        }
+    }
+
+    /// Passes on what the players other than `except` said meanwhile. Anything else they sent is rejected, it's not
+    /// their turn.
+    fn read_chat(&mut self, except: Option<PlayerId>) {
+        let ids: Vec<PlayerId> = self.connections.keys().copied().collect();
+        for id in ids.into_iter().filter(|e| Some(*e) != except) {
+            while let Some(connection) = self.connections.get_mut(&id) {
+                match connection.try_receive::<ClientMessage>() {
+                    Ok(Some(message)) => self.chat(id, message),
+                    Ok(None) => break,
+                    Err(_) => self.disconnect(id),
+                }
+            }
+        }
+    }
+
+    /// Records a chat message or emote of the player and sends it to every client. Messages that are too long or
+    /// sent too fast are rejected, just like anything but chat.
+    fn chat(&mut self, id: PlayerId, message: ClientMessage) {
+        let event = match message {
+            ClientMessage::Chat { text } => {
+                chat::check(&text).map(|text| Event::Chat { player: id, text })
+            }
+            ClientMessage::Emote { emote } => Ok(Event::Emote { player: id, emote }),
+            _ => {
+                let reason = i18n::text("net.not_your_turn");
+                self.send(id, &ServerMessage::Rejected { reason });
+                return;
+            }
+        };
+        let event = event.and_then(|e| self.chat_limit.count(id, Instant::now()).map(|_| e));
+
+        match event {
+            Ok(event) => {
+                self.game.print_events(std::slice::from_ref(&event));
+                self.game.record.events.push(event.clone());
+                let message = ServerMessage::Events {
+                    events: vec![event],
+                };
+                let ids: Vec<PlayerId> = self.connections.keys().copied().collect();
+                for id in ids {
+                    self.send(id, &message);
+                }
+                self.update_spectators(&[message]);
+            }
+            Err(e) => {
+                let reason = e.to_string();
+                self.send(id, &ServerMessage::Rejected { reason });
+            }
+        }
+    }
+
+    /// Chooses the action of a player who took too long and tells them about it.
+    fn time_out(&mut self, id: PlayerId) -> Result<Action, WizardError> {
+        println!(
+            "{}",
+            i18n::text_with("net.took_too_long", &[&self.game.player(id)?.name])
+        );
+        let action = match self.on_timeout {
+            TimeoutPolicy::AutoPlay => self.game.legal_actions()[0],
+            TimeoutPolicy::Bot(difficulty) => {
+                self.set_bot(id, Some(difficulty));
+                self.away.insert(id);
+                self.game.next_action()?.unwrap().1
+            }
+        };
+
+        self.send(id, &ServerMessage::TimedOut { action });
+        if self.away.contains(&id) {
+            // the player has to rejoin to take their seat back from the bot
+            self.connections.remove(&id);
+        }
+        Ok(action)
+    }
+
+    /// Seats the clients that returned with a valid session token and sends them the state of the table.
+    /// Returns the players who returned.
+    fn seat_returning(&mut self) -> Vec<PlayerId> {
+        let returning = std::mem::take(&mut *self.returning.lock().unwrap());
+        let mut seated = Vec::new();
+        for (token, mut connection) in returning {
+            let Some(id) = self.tokens.get(&token).copied() else {
+                let reason = i18n::text("net.no_session");
+                let _ = connection.send(&ServerMessage::Rejected { reason });
+                continue;
+            };
+
+            let welcome = ServerMessage::Welcome { player: id, token };
+            let state = ServerMessage::State {
+                view: Box::new(View::new(&self.game, Some(id))),
+            };
+            if connection.send(&welcome).is_ok() && connection.send(&state).is_ok() {
+                if self.away.remove(&id) {
+                    self.set_bot(id, None);
+                }
+                if let Ok(player) = self.game.player(id) {
+                    println!("{}", i18n::text_with("net.back", &[&player.name]));
+                }
+                self.connections.insert(id, connection);
+                seated.push(id);
+            }
+        }
+        seated
+    }
+
+    fn set_bot(&mut self, id: PlayerId, bot: Option<Difficulty>) {
+        if let Some(player) = self.game.players.iter_mut().find(|e| e.id == id) {
+            player.bot = bot;
+        }
+    }
+
+    /// Sends the message to the given player. A player whose connection fails is disconnected.
+    fn send(&mut self, id: PlayerId, message: &ServerMessage) {
+        if let Some(connection) = self.connections.get_mut(&id) {
+            if connection.send(message).is_err() {
+                self.disconnect(id);
+            }
+        }
+    }
+
+    fn disconnect(&mut self, id: PlayerId) {
+        if self.connections.remove(&id).is_some() {
+            if let Ok(player) = self.game.player(id) {
+                println!(
+                    "{}",
+                    i18n::text_with("net.lost_connection", &[&player.name])
+                );
+            }
+        }
+    }
+
+    /// Sends the given events and the new state of the table to every client.
+    fn broadcast(&mut self, events: &[Event]) {
+        if events.is_empty() {
+            return;
+        }
+        let ids: Vec<PlayerId> = self.connections.keys().copied().collect();
+        for id in ids {
+            let events = events.iter().map(|e| View::redact(e, Some(id))).collect();
+            self.send(id, &ServerMessage::Events { events });
+            let view = Box::new(View::new(&self.game, Some(id)));
+            self.send(id, &ServerMessage::State { view });
+        }
+
+        let mut messages = vec![
+            ServerMessage::Events {
+                events: events.iter().map(|e| View::redact(e, None)).collect(),
+            },
+            ServerMessage::State {
+                view: Box::new(View::new(&self.game, None)),
+            },
+        ];
+        if let Some(delay) = self.reveal_hands {
+            if self.game.phase == Phase::RoundScored {
+                messages.extend(self.hands_until(self.game.round_number.saturating_sub(delay)));
+            }
+        }
+        self.update_spectators(&messages);
+    }
+
+    /// Returns the hands of the rounds up to the given round that weren't shown to the spectators yet.
+    fn hands_until(&mut self, round: u32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        for event in &self.game.record.events {
+            if let Event::RoundStarted {
+                round: number,
+                hands,
+                ..
+            } = event
+            {
+                if *number > self.revealed && *number <= round {
+                    messages.push(ServerMessage::Hands {
+                        round: *number,
+                        hands: hands.clone(),
+                    });
+                }
+            }
+        }
+        self.revealed = self.revealed.max(round);
+        messages
+    }
+
+    /// Sends the messages to every spectator. Anything a spectator sent is rejected, spectators who left are removed.
+    fn update_spectators(&self, messages: &[ServerMessage]) {
+        let rejected = ServerMessage::Rejected {
+            reason: i18n::text("net.spectators_cant_act"),
+        };
+        self.spectators.lock().unwrap().retain_mut(|connection| {
+            loop {
+                match connection.try_receive::<ClientMessage>() {
+                    Ok(Some(_)) if connection.send(&rejected).is_ok() => continue,
+                    Ok(None) => break,
+                    _ => return false,
+                }
+            }
+            messages.iter().all(|e| connection.send(e).is_ok())
+        });
+    }
+}
+
+/// A player or spectator who joined a game over the network.
+pub struct Client {
+    pub connection: Connection,
+    /// The seat of the client, `None` for a spectator.
+    pub player: Option<PlayerId>,
+    /// The session token of the seat, see `Client::rejoin`.
+    pub token: Option<String>,
+    /// The players of the game, known once the game started.
+    pub players: Vec<Player>,
+}
+
+impl Client {
+    /// Connects to the server at the given address and takes a seat with the given name.
+    pub fn join(address: impl ToSocketAddrs, name: &str) -> Result<Self, WizardError> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        connection.send(&ClientMessage::Join {
+            name: name.to_string(),
+        })?;
+        Client::welcome(connection)
+    }
+
+    /// Connects to the server at the given address again and takes the seat of the given session token.
+    pub fn rejoin(address: impl ToSocketAddrs, token: &str) -> Result<Self, WizardError> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        connection.send(&ClientMessage::Rejoin {
+            token: token.to_string(),
+        })?;
+        Client::welcome(connection)
+    }
+
+    /// Waits until the server seats the client, after it asked for a seat on the given connection.
+    pub fn welcome(mut connection: Connection) -> Result<Self, WizardError> {
+        match connection.receive()? {
+            ServerMessage::Welcome { player, token } => Ok(Client {
+                connection,
+                player: Some(player),
+                token: Some(token),
+                players: Vec::new(),
+            }),
+            ServerMessage::Rejected { reason } => Err(WizardError::Network(reason)),
+            message => Err(WizardError::Network(i18n::text_with(
+                "net.unexpected",
+                &[&format!("{:?}", message)],
+            ))),
+        }
+    }
+
+    /// Connects to the server at the given address to watch the game.
+    pub fn watch(address: impl ToSocketAddrs, name: &str) -> Result<Self, WizardError> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        connection.send(&ClientMessage::Watch {
+            name: name.to_string(),
+        })?;
+        Client::watching(connection)
+    }
+
+    /// Waits until the server lets the client watch, after it asked to watch on the given connection.
+    pub fn watching(mut connection: Connection) -> Result<Self, WizardError> {
+        match connection.receive()? {
+            ServerMessage::Watching => Ok(Client {
+                connection,
+                player: None,
+                token: None,
+                players: Vec::new(),
+            }),
+            ServerMessage::Rejected { reason } => Err(WizardError::Network(reason)),
+            message => Err(WizardError::Network(i18n::text_with(
+                "net.unexpected",
+                &[&format!("{:?}", message)],
+            ))),
+        }
+    }
+
+    /// Plays or watches until the game is over and returns the final scores. `choose` is asked for an action whenever
+    /// it's the turn of the client, every other message is passed to `show`. <br>
+    /// `choose` may also answer with a chat message or emote, it's asked again until it returns an action.
+    pub fn play<M: Into<ClientMessage>>(
+        &mut self,
+        mut choose: impl FnMut(&View) -> Result<M, WizardError>,
+        mut show: impl FnMut(&Client, &ServerMessage),
+    ) -> Result<Vec<(String, i32)>, WizardError> {
+        loop {
+            let message: ServerMessage = self.connection.receive()?;
+            match &message {
+                ServerMessage::Turn { view } => {
+                    loop {
+                        let answer = choose(view)?.into();
+                        self.connection.send(&answer)?;
+                        if matches!(answer, ClientMessage::Act { .. }) {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                ServerMessage::GameOver { scores } => return Ok(scores.clone()),
+                ServerMessage::Events { events } => {
+                    for event in events {
+                        if let Event::GameStarted { players } = event {
+                            self.players = players.clone();
+                        }
+                    }
+                }
+                // spectators may come late and miss the start of the game
+                ServerMessage::State { view } => self.players = view.players.clone(),
+                _ => {}
+            }
+            show(self, &message);
+        }
+    }
+
+    /// Returns the name of the given player.
+    pub fn name(&self, id: PlayerId) -> String {
+        self.players
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| e.name.clone())
+            .unwrap_or_else(|| i18n::text_with("player.unknown", &[&id.0]))
+    }
+}
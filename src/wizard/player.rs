@@ -1,72 +1,343 @@
-use inquire::{Select, Text};
+use inquire::{Confirm, Select, Text};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
 
-use crate::wizard::card::Card;
+use crate::wizard::bot::Difficulty;
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::profile::Profile;
 
-#[derive(PartialEq, Debug, Clone)]
+/// The identity of a player. It stays the same while the player is dealt cards, guesses and scores,
+/// so the game state, the record and the front ends refer to players by it.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PlayerId(pub u32);
+
+impl PlayerId {
+    /// Returns an id that differs from all ids returned before.
+    pub fn next() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(1);
+        PlayerId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A player at the table. The cards, guesses and scores of a game are kept by the `Game`.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
+    pub id: PlayerId,
     pub name: String,
-    pub cards: Vec<Card>,
-    pub guess_stitches: u32,
-    pub actual_stitches: u32,
+    /// Whether the player gets suggestions for bids and cards.
+    pub advisor: bool,
+    /// The difficulty of a computer controlled player. `None` for humans.
+    pub bot: Option<Difficulty>,
+    /// The command that starts the program of an external bot, see `external::ExternalBot`. `None` for everyone else.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external: Option<String>,
 }
 
 impl Player {
-    /// This functions will return a default `Player` with no cards and a name that was choosen by the user.
+    /// Returns a human `Player` with a new id and the given name.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// let my_player: Player = Player::new(1); // user is asked to input his name
+    /// # use wizard::wizard::player::Player;
+    /// let player: Player = Player::with_name("Max".to_string());
+    /// assert_ne!(player.id, Player::with_name("Max".to_string()).id);
     /// ```
-    pub fn new(index: u32) -> Self {
-        // get input username
-        loop {
-            let input = Text::new(format!("Player {}: What's your name?", index).as_str()).prompt();
-
-            match input {
-                Ok(name) => {
-                    if name.len() <= 1 {
-                        println!("Your name is to short.")
-                    } else {
-                        return Player {
-                            name,
-                            cards: Vec::new(),
-                            guess_stitches: 0,
-                            actual_stiches: 0,
-                        };
+    pub fn with_name(name: String) -> Self {
+        Player {
+            id: PlayerId::next(),
+            name,
+            advisor: false,
+            bot: None,
+            external: None,
+        }
+    }
+
+    /// Returns a computer controlled `Player` with the given difficulty. <br>
+    /// The number tells bots of the same difficulty apart, the first one has no number in its name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::bot::Difficulty;
+    /// # use wizard::wizard::player::Player;
+    /// assert_eq!(Player::bot(Difficulty::Hard, 1).name, "Hard Bot");
+    /// assert_eq!(Player::bot(Difficulty::Easy, 2).name, "Easy Bot 2");
+    /// ```
+    pub fn bot(difficulty: Difficulty, number: u32) -> Self {
+        let name = match number {
+            1 => i18n::text_with("player.bot", &[&difficulty.name()]),
+            _ => i18n::text_with("player.bot_numbered", &[&difficulty.name(), &number]),
+        };
+
+        Player {
+            bot: Some(difficulty),
+            ..Player::with_name(name)
+        }
+    }
+
+    /// Returns an external bot that is played by the program the command starts. It's named after the program.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::player::Player;
+    /// let player = Player::external("python3 bots/greedy.py --fast");
+    /// assert_eq!(player.name, "greedy.py");
+    /// assert_eq!(player.external.as_deref(), Some("python3 bots/greedy.py --fast"));
+    /// ```
+    pub fn external(command: &str) -> Self {
+        // the first argument is the script for interpreters like python3
+        let mut words = command.split_whitespace();
+        let fallback = i18n::text("player.external_bot");
+        let program = match (words.next(), words.next()) {
+            (Some(_), Some(script)) if script.contains(['.', '/']) && !script.starts_with('-') => {
+                script
+            }
+            (Some(program), _) => program,
+            _ => fallback.as_str(),
+        };
+        let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+        Player {
+            external: Some(command.to_string()),
+            ..Player::with_name(name.to_string())
+        }
+    }
+
+    /// Returns the players of a comma separated list such as `"Ann,Bob,bot:hard"`. Bots are written as `bot:` followed by
+    /// their difficulty and are numbered like in `Player::bot`, external bots as `external:` followed by their
+    /// command. A name that is already taken gets a number as in `Player::unique_name`. <br>
+    /// `WizardError::InvalidPlayer` is returned if an entry is too short or names an unknown difficulty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::player::Player;
+    /// let players = Player::parse_list("Ann, bot:hard,bot:Hard, external:./greedy")?;
+    /// let names: Vec<&str> = players.iter().map(|e| e.name.as_str()).collect();
+    /// assert_eq!(names, vec!["Ann", "Hard Bot", "Hard Bot 2", "greedy"]);
+    /// assert_eq!(Player::parse_list("Ann,Ann")?[1].name, "Ann 2");
+    /// assert!(Player::parse_list("Ann,bot:brilliant").is_err());
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn parse_list(list: &str) -> Result<Vec<Player>, WizardError> {
+        let mut players: Vec<Player> = Vec::new();
+        for entry in list.split(',').map(|e| e.trim()) {
+            let mut player = match entry.strip_prefix("bot:") {
+                Some(name) => {
+                    let difficulty = Difficulty::from_name(name)
+                        .ok_or_else(|| WizardError::InvalidPlayer(entry.to_string()))?;
+                    let number = players.iter().filter(|e| e.bot == Some(difficulty)).count();
+                    Player::bot(difficulty, number as u32 + 1)
+                }
+                None if entry.starts_with("external:") => {
+                    let command = &entry["external:".len()..];
+                    if command.trim().is_empty() {
+                        return Err(WizardError::InvalidPlayer(entry.to_string()));
                     }
+                    Player::external(command)
                 }
-                Err(e) => println!("Error reading input. ({})", e),
-            }
+                None if entry.len() > 1 => Player::with_name(entry.to_string()),
+                None => return Err(WizardError::InvalidPlayer(entry.to_string())),
+            };
+            player.name = Player::unique_name(&player.name, &players);
+            players.push(player);
         }
+        Ok(players)
     }
 
-    /// This function is called on a `Player` and will take in a vector of `u32`s, which are the possible options for the player. The player will then be asked to select how many stitches he want's to get.
+    /// Returns the name, followed by the lowest number from 2 on that makes it differ from the names of the given
+    /// players if one of them already has it. Profiles and records tell players apart by their names.
     ///
     /// # Examples
     ///
     /// ```rust
     /// # use wizard::wizard::player::Player;
+    /// let players = Player::parse_list("Ann,Ann 2,Bob")?;
+    /// assert_eq!(Player::unique_name("Ann", &players), "Ann 3");
+    /// assert_eq!(Player::unique_name("Carl", &players), "Carl");
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn unique_name(name: &str, players: &[Player]) -> String {
+        let taken = |name: &str| players.iter().any(|e| e.name == name);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|number| format!("{} {}", name, number))
+            .find(|e| !taken(e))
+            .unwrap()
+    }
+
+    /// This functions will return a default `Player` with a name that was choosen by the user.
     ///
-    /// let mut player: Player = Player::new(1);
+    /// # Examples
     ///
-    /// let player.guess_stitches = player.guess_stitches(&self, vec![1, 2, 3, 5]); // player will be asked to select his stitches
+    /// ```rust,no_run
+    /// # use wizard::wizard::player::Player;
+    /// let my_player: Player = Player::new(1)?; // user is asked to input his name
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
     /// ```
-    pub fn guess_stitches(&self, options: Vec<u32>) -> u32 {
+    pub fn new(index: u32) -> Result<Self, WizardError> {
+        // get input username
         loop {
-            let input = Select::new(
-                format!("{}: Select how many stitches you want to get.", self.name).as_str(),
-                options.iter().map(|x| x.to_string()).collect(),
-            )
-            .prompt();
-            match input {
-                Ok(guess) => {
-                    return guess.trim().parse().unwrap();
-                }
-                Err(e) => {
-                    println!("Error reading input. ({})", e)
-                }
+            let name =
+                Text::new(i18n::text_with("player.ask_name", &[&index]).as_str()).prompt()?;
+
+            if name.len() <= 1 {
+                println!("{}", i18n::text("player.name_too_short"))
+            } else {
+                return Ok(Player::with_name(name));
             }
         }
     }
+
+    /// The user is asked to select one of the given existing profiles, which are shown with their rating. A new player can be created with `Player::new` instead.
+    /// The profiles of the given players who were already chosen aren't offered, and a new player whose name one of
+    /// them has gets a number, see `Player::unique_name`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use wizard::wizard::player::Player;
+    /// # use wizard::wizard::profile::ProfileStore;
+    /// let store = ProfileStore::default();
+    /// let my_player: Player = Player::select(1, &store.profiles, &[])?; // user is asked to select a profile
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn select(
+        index: u32,
+        profiles: &[Profile],
+        chosen: &[Player],
+    ) -> Result<Self, WizardError> {
+        let profiles: Vec<&Profile> = profiles
+            .iter()
+            .filter(|e| chosen.iter().all(|p| p.name != e.name))
+            .collect();
+        let new_player = || {
+            let mut player = Player::new(index)?;
+            player.name = Player::unique_name(&player.name, chosen);
+            Ok(player)
+        };
+        if profiles.is_empty() {
+            return new_player();
+        }
+
+        let mut options: Vec<String> = profiles
+            .iter()
+            .map(|e| i18n::text_with("player.profile", &[&e.name, &format!("{:.0}", e.rating)]))
+            .collect();
+        options.push(i18n::text("player.new_player"));
+
+        let selected = Select::new(
+            i18n::text_with("player.ask_profile", &[&index]).as_str(),
+            options,
+        )
+        .raw_prompt()?;
+
+        if selected.index == profiles.len() {
+            new_player()
+        } else {
+            Ok(Player::with_name(profiles[selected.index].name.clone()))
+        }
+    }
+
+    /// This function is called on a `Player` and will take in a vector of `u32`s, which are the possible options for the player. The player will then be asked to select how many stitches he want's to get.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use wizard::wizard::player::Player;
+    ///
+    /// let player: Player = Player::new(1)?;
+    ///
+    /// let guess: u32 = player.guess_stitches(vec![1, 2, 3, 5])?; // player will be asked to select his stitches
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn guess_stitches(&self, options: Vec<u32>) -> Result<u32, WizardError> {
+        let selected = Select::new(
+            i18n::text_with("player.ask_guess", &[&self.name]).as_str(),
+            options.iter().map(|x| x.to_string()).collect(),
+        )
+        .raw_prompt()?;
+
+        Ok(options[selected.index])
+    }
+
+    /// This function is called on a `Player` and will take in the cards the player is allowed to play. The player will then be asked to select one of them. <br>
+    /// If a recommended card is given it is marked and preselected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use wizard::wizard::card::{Card, CardColor};
+    /// # use wizard::wizard::player::Player;
+    ///
+    /// let player: Player = Player::new(1)?;
+    ///
+    /// let options = vec![Card::Wizard, Card::Number(4, CardColor::RED)];
+    /// let card: Card = player.play_card(options, Some(Card::Wizard))?; // player will be asked to select a card
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn play_card(
+        &self,
+        options: Vec<Card>,
+        recommended: Option<Card>,
+    ) -> Result<Card, WizardError> {
+        let names: Vec<String> = options
+            .iter()
+            .map(|card| {
+                if Some(*card) == recommended {
+                    i18n::text_with("player.recommended", &[&card.name()])
+                } else {
+                    card.name()
+                }
+            })
+            .collect();
+        let cursor = recommended
+            .and_then(|recommended| options.iter().position(|card| *card == recommended))
+            .unwrap_or(0);
+
+        let selected = Select::new(
+            i18n::text_with("player.ask_card", &[&self.name]).as_str(),
+            names,
+        )
+        .with_starting_cursor(cursor)
+        .raw_prompt()?;
+
+        Ok(options[selected.index])
+    }
+
+    /// The player will be asked to select the main color of the round. This is the case if a Wizard is revealed as the trump card.
+    pub fn choose_color(&self) -> Result<CardColor, WizardError> {
+        let colors = CardColor::all_colors();
+
+        let selected = Select::new(
+            i18n::text_with("player.ask_color", &[&self.name]).as_str(),
+            colors.iter().map(|color| color.name()).collect(),
+        )
+        .raw_prompt()?;
+
+        Ok(colors[selected.index])
+    }
+
+    /// The player will be asked whether they want to take back the described move, e.g. "your guess of 2".
+    pub fn take_back(&self, action: &str) -> Result<bool, WizardError> {
+        Ok(
+            Confirm::new(i18n::text_with("player.ask_take_back", &[&self.name, &action]).as_str())
+                .with_default(false)
+                .prompt()?,
+        )
+    }
+
+    /// The player will be asked whether another player may take back the described move.
+    pub fn allow_take_back(&self, name: &str, action: &str) -> Result<bool, WizardError> {
+        Ok(Confirm::new(
+            i18n::text_with("player.ask_allow_take_back", &[&self.name, &name, &action]).as_str(),
+        )
+        .with_default(true)
+        .prompt()?)
+    }
 }
@@ -0,0 +1,303 @@
+use crate::wizard::bot::Difficulty;
+use crate::wizard::i18n;
+use crate::wizard::player::Player;
+use crate::wizard::rating::{self, INITIAL_RATING};
+use crate::wizard::record::GameRecord;
+use crate::wizard::report::Report;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How often a player got the guessed stitches.
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BidStats {
+    pub rounds: u32,
+    pub hit: u32,
+}
+
+impl BidStats {
+    /// Returns the share of rounds with a correct guess, between 0 and 1.
+    pub fn rate(&self) -> f64 {
+        if self.rounds == 0 {
+            0.0
+        } else {
+            self.hit as f64 / self.rounds as f64
+        }
+    }
+}
+
+/// The long-term statistics of a player.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub games_played: u32,
+    pub wins: u32,
+    pub total_score: i64,
+    pub bids: BidStats,
+    /// The bid statistics per amount of cards in a round.
+    pub bids_by_round_size: BTreeMap<u32, BidStats>,
+    #[serde(default = "initial_rating")]
+    pub rating: f64,
+    /// The difficulty of a computer controlled player. `None` for humans.
+    #[serde(default)]
+    pub bot: Option<Difficulty>,
+}
+
+fn initial_rating() -> f64 {
+    INITIAL_RATING
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: String::new(),
+            games_played: 0,
+            wins: 0,
+            total_score: 0,
+            bids: BidStats::default(),
+            bids_by_round_size: BTreeMap::new(),
+            rating: INITIAL_RATING,
+            bot: None,
+        }
+    }
+}
+
+impl Profile {
+    pub fn average_score(&self) -> f64 {
+        if self.games_played == 0 {
+            0.0
+        } else {
+            self.total_score as f64 / self.games_played as f64
+        }
+    }
+}
+
+/// The final scores of a finished game.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameResult {
+    pub scores: Vec<(String, i32)>,
+    /// The lobby room the game was played in.
+    #[serde(default)]
+    pub room: Option<String>,
+}
+
+/// All profiles and game results, stored as a JSON file.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use wizard::wizard::profile::ProfileStore;
+/// let path = ProfileStore::default_path().unwrap();
+/// let store = ProfileStore::load(&path).unwrap();
+/// println!("{}", store.stats());
+/// ```
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProfileStore {
+    pub profiles: Vec<Profile>,
+    pub games: Vec<GameResult>,
+}
+
+impl ProfileStore {
+    /// Returns the path of the profile store in the data directory of the user, e.g. `~/.local/share/wizard/profiles.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|e| e.join("wizard").join("profiles.json"))
+    }
+
+    /// Reads the store from the given path. An empty store is returned if the file does not exist yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ProfileStore::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the store as JSON to the given path. Missing directories are created.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.iter().map(|e| e.name.clone()).collect()
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|e| e.name == name)
+    }
+
+    /// Returns the profile of the given player. Humans are found by their name, bots by their difficulty, so
+    /// `"Hard Bot"` and `"Hard Bot 2"` share a profile.
+    pub fn player_profile(&self, player: &Player) -> Option<&Profile> {
+        self.position(&player.name, player.bot)
+            .map(|index| &self.profiles[index])
+    }
+
+    fn position(&self, name: &str, bot: Option<Difficulty>) -> Option<usize> {
+        match bot {
+            Some(_) => self.profiles.iter().position(|e| e.bot == bot),
+            None => self
+                .profiles
+                .iter()
+                .position(|e| e.bot.is_none() && e.name == name),
+        }
+    }
+
+    /// Adds a finished game to the statistics of its players and updates their ratings. Profiles are created for new players,
+    /// bots of the same difficulty share one profile and every seat of them counts as a game. <br>
+    /// All players with the highest score win the game.
+    pub fn record_game(&mut self, record: &GameRecord) {
+        let report = Report::new(record);
+        let best = report.players.iter().map(|e| e.score).max();
+
+        let indices: Vec<usize> = report
+            .players
+            .iter()
+            .map(|player| {
+                let bot = record.player(player.id).and_then(|e| e.bot);
+                match self.position(&player.name, bot) {
+                    Some(index) => index,
+                    None => {
+                        let name = match bot {
+                            Some(difficulty) => Player::bot(difficulty, 1).name,
+                            None => player.name.clone(),
+                        };
+                        self.profiles.push(Profile {
+                            name,
+                            bot,
+                            ..Profile::default()
+                        });
+                        self.profiles.len() - 1
+                    }
+                }
+            })
+            .collect();
+
+        let ratings: Vec<(f64, i32)> = indices
+            .iter()
+            .zip(&report.players)
+            .map(|(index, player)| (self.profiles[*index].rating, player.score))
+            .collect();
+        let changes = rating::rating_changes(&ratings);
+
+        for ((index, player), change) in indices.iter().zip(&report.players).zip(changes) {
+            let profile = &mut self.profiles[*index];
+
+            profile.rating += change;
+            profile.games_played += 1;
+            profile.total_score += player.score as i64;
+            if Some(player.score) == best {
+                profile.wins += 1;
+            }
+            for round in &player.rounds {
+                let hit = (round.guess_stitches == round.actual_stitches) as u32;
+                let by_size = profile.bids_by_round_size.entry(round.cards).or_default();
+                for stats in [&mut profile.bids, by_size] {
+                    stats.rounds += 1;
+                    stats.hit += hit;
+                }
+            }
+        }
+
+        self.games.push(GameResult {
+            scores: indices
+                .iter()
+                .zip(&report.players)
+                .map(|(index, player)| (self.profiles[*index].name.clone(), player.score))
+                .collect(),
+            room: None,
+        });
+    }
+
+    /// Returns the profiles sorted by wins, then by average score.
+    pub fn leaderboard(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self.profiles.iter().collect();
+        profiles.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.average_score().total_cmp(&a.average_score()))
+        });
+        profiles
+    }
+
+    /// Returns how often the first player scored more, less or the same as the second player
+    /// in the games they both played. (wins, losses, draws)
+    pub fn head_to_head(&self, player: &str, opponent: &str) -> (u32, u32, u32) {
+        let mut out = (0, 0, 0);
+        for game in &self.games {
+            let score = |name: &str| game.scores.iter().find(|e| e.0 == name).map(|e| e.1);
+            if let (Some(a), Some(b)) = (score(player), score(opponent)) {
+                match a.cmp(&b) {
+                    std::cmp::Ordering::Greater => out.0 += 1,
+                    std::cmp::Ordering::Less => out.1 += 1,
+                    std::cmp::Ordering::Equal => out.2 += 1,
+                }
+            }
+        }
+        out
+    }
+
+    /// Returns the leaderboard, the bid accuracy per round size and the head-to-head records as printable text.
+    pub fn stats(&self) -> String {
+        if self.profiles.is_empty() {
+            return format!("{}\n", i18n::text("stats.no_games"));
+        }
+
+        let mut out = format!("{}\n", i18n::text("stats.leaderboard"));
+        out.push_str(&format!(
+            "{:>3}  {:<16} {:>6} {:>5} {:>5} {:>10} {:>13}\n",
+            "#",
+            i18n::text("table.name"),
+            i18n::text("stats.rating"),
+            i18n::text("table.games"),
+            i18n::text("stats.wins"),
+            i18n::text("stats.average_score"),
+            i18n::text("stats.bid_accuracy")
+        ));
+        let leaderboard = self.leaderboard();
+        for (place, profile) in leaderboard.iter().enumerate() {
+            out.push_str(&format!(
+                "{:>3}  {:<16} {:>6.0} {:>5} {:>5} {:>10.1} {:>12.0}%\n",
+                place + 1,
+                profile.name,
+                profile.rating,
+                profile.games_played,
+                profile.wins,
+                profile.average_score(),
+                profile.bids.rate() * 100.0
+            ));
+        }
+
+        out.push_str(&format!("\n{}\n", i18n::text("stats.by_round_size")));
+        for profile in &leaderboard {
+            let sizes: Vec<String> = profile
+                .bids_by_round_size
+                .iter()
+                .map(|(size, stats)| format!("{}: {:.0}%", size, stats.rate() * 100.0))
+                .collect();
+            out.push_str(&format!("{}: {}\n", profile.name, sizes.join(", ")));
+        }
+
+        out.push_str(&format!("\n{}\n", i18n::text("stats.head_to_head")));
+        for (i, player) in leaderboard.iter().enumerate() {
+            for opponent in &leaderboard[i + 1..] {
+                let (wins, losses, draws) = self.head_to_head(&player.name, &opponent.name);
+                if wins + losses + draws > 0 {
+                    out.push_str(&format!(
+                        "{}\n",
+                        i18n::text_with(
+                            "stats.versus",
+                            &[&player.name, &opponent.name, &wins, &losses, &draws]
+                        )
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,61 @@
+/// The rating of a player without any games.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The maximum change of a rating after a game against a single opponent with a small margin.
+pub const K_FACTOR: f64 = 32.0;
+
+/// Returns the expected result of a player against an opponent, between 0 (loss) and 1 (win).
+pub fn expected_result(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Returns how much a margin of the given amount of points counts, between 1 and 2. <br>
+/// A win by 100 points or more counts twice as much as a win by a single point.
+pub fn margin_factor(margin: u32) -> f64 {
+    1.0 + (margin as f64 / 100.0).min(1.0)
+}
+
+/// Calculates the rating changes of all players of a finished game. <br>
+/// The game is treated as a set of duels between every pair of players, which are decided by the final scores.
+/// Each duel is weighted with the margin of the scores, and the changes are averaged over the opponents.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::rating;
+/// // (rating, final score)
+/// let players = [(1500.0, 120), (1500.0, 80), (1600.0, 80)];
+/// let changes = rating::rating_changes(&players);
+///
+/// assert!(changes[0] > 0.0);
+/// assert!(changes[2] < changes[1]);
+/// assert!(changes.iter().sum::<f64>().abs() < 1e-9);
+/// ```
+pub fn rating_changes(players: &[(f64, i32)]) -> Vec<f64> {
+    if players.len() < 2 {
+        return vec![0.0; players.len()];
+    }
+
+    players
+        .iter()
+        .enumerate()
+        .map(|(i, (rating, score))| {
+            let total: f64 = players
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| i != *j)
+                .map(|(_, (opponent, opponent_score))| {
+                    let result = match score.cmp(opponent_score) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    margin_factor(score.abs_diff(*opponent_score))
+                        * (result - expected_result(*rating, *opponent))
+                })
+                .sum();
+
+            K_FACTOR * total / (players.len() - 1) as f64
+        })
+        .collect()
+}
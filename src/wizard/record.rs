@@ -0,0 +1,199 @@
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::chat::Emote;
+use crate::wizard::i18n;
+use crate::wizard::player::{Player, PlayerId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Something that happened in a game. Players are referred to by their id, `GameStarted` tells who they are.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum Event {
+    /// The players of the game in seating order.
+    GameStarted {
+        players: Vec<Player>,
+    },
+    /// The cards were dealt. The hands are listed in the order the players guess in.
+    RoundStarted {
+        round: u32,
+        hands: Vec<(PlayerId, Vec<Card>)>,
+        trump_card: Option<Card>,
+        main_color: Option<CardColor>,
+    },
+    Guessed {
+        player: PlayerId,
+        stitches: u32,
+    },
+    Played {
+        player: PlayerId,
+        card: Card,
+    },
+    TrickWon {
+        player: PlayerId,
+    },
+    /// The points every player got in this round.
+    RoundScored {
+        points: Vec<(PlayerId, i32)>,
+    },
+    /// A player said something at the table of a network game.
+    Chat {
+        player: PlayerId,
+        text: String,
+    },
+    Emote {
+        player: PlayerId,
+        emote: Emote,
+    },
+}
+
+impl Event {
+    /// Returns a line of text for everything that happened. The hands of `RoundStarted` are shown unless they are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::player::Player;
+    /// # use wizard::wizard::record::Event;
+    /// let max = Player::with_name("Max".to_string());
+    /// let event = Event::Guessed { player: max.id, stitches: 2 };
+    /// assert_eq!(event.describe(&[max]), vec!["Max guesses 2."]);
+    /// ```
+    pub fn describe(&self, players: &[Player]) -> Vec<String> {
+        let name = |id: &PlayerId| {
+            players
+                .iter()
+                .find(|e| e.id == *id)
+                .map(|e| e.name.clone())
+                .unwrap_or_else(|| i18n::text_with("player.unknown", &[&id.0]))
+        };
+        let card_names = |cards: &[Card]| {
+            let names: Vec<String> = cards.iter().map(|e| e.name()).collect();
+            names.join(", ")
+        };
+
+        match self {
+            Event::GameStarted { players } => {
+                let names: Vec<&str> = players.iter().map(|e| e.name.as_str()).collect();
+                vec![i18n::text_with("event.game_started", &[&names.join(", ")])]
+            }
+            Event::RoundStarted {
+                round,
+                hands,
+                trump_card,
+                main_color,
+            } => {
+                let mut lines = vec![i18n::text_with("event.round_started", &[round])];
+                for (player, hand) in hands.iter().filter(|e| !e.1.is_empty()) {
+                    lines.push(i18n::text_with(
+                        "event.cards",
+                        &[&name(player), &card_names(hand)],
+                    ));
+                }
+                lines.push(Event::trump_text(trump_card, main_color));
+                lines
+            }
+            Event::Guessed { player, stitches } => {
+                vec![i18n::text_with("event.guessed", &[&name(player), stitches])]
+            }
+            Event::Played { player, card } => {
+                vec![i18n::text_with(
+                    "event.played",
+                    &[&name(player), &card.name()],
+                )]
+            }
+            Event::TrickWon { player } => {
+                vec![i18n::text_with("event.trick_won", &[&name(player)])]
+            }
+            Event::RoundScored { points } => points
+                .iter()
+                .map(|(player, points)| {
+                    i18n::text_with("event.points", &[&name(player), &format!("{:+}", points)])
+                })
+                .collect(),
+            Event::Chat { player, text } => {
+                vec![i18n::text_with("event.says", &[&name(player), text])]
+            }
+            Event::Emote { player, emote } => {
+                vec![i18n::text_with(
+                    "event.says",
+                    &[&name(player), &emote.text()],
+                )]
+            }
+        }
+    }
+
+    /// Returns the line that tells the trump card and the main color of a round.
+    pub fn trump_text(trump_card: &Option<Card>, main_color: &Option<CardColor>) -> String {
+        match (trump_card, main_color) {
+            (Some(card), Some(color)) => {
+                i18n::text_with("event.trump", &[&card.name(), &color.name()])
+            }
+            (Some(card), None) => i18n::text_with("event.trump_without_color", &[&card.name()]),
+            _ => i18n::text("event.no_trump"),
+        }
+    }
+}
+
+/// The history of a game as a list of `Event`s. It can be saved to and loaded from a JSON file.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub events: Vec<Event>,
+}
+
+impl GameRecord {
+    /// Returns the players of the game in seating order, empty if the game wasn't started.
+    pub fn players(&self) -> Vec<Player> {
+        self.events
+            .iter()
+            .find_map(|event| match event {
+                Event::GameStarted { players } => Some(players.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the names of all players in seating order.
+    pub fn player_names(&self) -> Vec<String> {
+        self.players().into_iter().map(|e| e.name).collect()
+    }
+
+    /// Returns the player with the given id.
+    pub fn player(&self, id: PlayerId) -> Option<Player> {
+        self.players().into_iter().find(|e| e.id == id)
+    }
+
+    /// Returns the name of the player with the given id. Unknown players are called by their id, e.g. `"Player 7"`.
+    pub fn name(&self, id: PlayerId) -> String {
+        self.player(id)
+            .map(|e| e.name)
+            .unwrap_or_else(|| i18n::text_with("player.unknown", &[&id.0]))
+    }
+
+    /// Returns the total score of every player.
+    pub fn scores(&self) -> BTreeMap<PlayerId, i32> {
+        let mut scores: BTreeMap<PlayerId, i32> =
+            self.players().into_iter().map(|e| (e.id, 0)).collect();
+        for event in &self.events {
+            if let Event::RoundScored { points } = event {
+                for (player, points) in points {
+                    *scores.entry(*player).or_insert(0) += points;
+                }
+            }
+        }
+        scores
+    }
+
+    /// Writes the record as JSON to the given path.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Reads a record from a JSON file at the given path.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
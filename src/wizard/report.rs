@@ -0,0 +1,259 @@
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::game::Game;
+use crate::wizard::i18n;
+use crate::wizard::player::PlayerId;
+use crate::wizard::record::{Event, GameRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A play where a different playable card would have brought the player closer to the guess.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Moment {
+    /// The number of the trick in the round, starting at 1.
+    pub trick: u32,
+    pub played: Card,
+    pub alternatives: Vec<Card>,
+    /// Whether the alternatives would have won the trick. Otherwise they would have lost it.
+    pub would_win: bool,
+}
+
+impl Moment {
+    /// Returns a short description, e.g. `"Red 4 or Blue 2 instead of Wizard would have lost the trick."`
+    pub fn description(&self) -> String {
+        let alternatives: Vec<String> = self.alternatives.iter().map(|e| e.name()).collect();
+        let key = if self.would_win {
+            "report.would_have_won"
+        } else {
+            "report.would_have_lost"
+        };
+        i18n::text_with(
+            key,
+            &[
+                &alternatives.join(&i18n::text("report.or")),
+                &self.played.name(),
+            ],
+        )
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RoundReport {
+    pub round: u32,
+    /// The amount of cards every player got.
+    pub cards: u32,
+    pub guess_stitches: u32,
+    pub actual_stitches: u32,
+    pub points: i32,
+    /// The difference to the points of a correct guess.
+    pub points_lost: i32,
+    pub moments: Vec<Moment>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerReport {
+    pub id: PlayerId,
+    pub name: String,
+    pub score: i32,
+    pub rounds: Vec<RoundReport>,
+}
+
+/// The post-game analysis of a `GameRecord` with a report per player.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub players: Vec<PlayerReport>,
+}
+
+/// A card in the current trick together with the cards the player could have played instead.
+struct Play {
+    player: PlayerId,
+    card: Card,
+    options: Vec<Card>,
+}
+
+impl Report {
+    /// Analyses the given record. Every trick is replayed with all other playable cards of each player,
+    /// while the cards of the other players stay the same.
+    pub fn new(record: &GameRecord) -> Self {
+        let mut players: Vec<PlayerReport> = record
+            .players()
+            .into_iter()
+            .map(|player| PlayerReport {
+                id: player.id,
+                name: player.name,
+                score: 0,
+                rounds: Vec::new(),
+            })
+            .collect();
+
+        let mut round = 0;
+        let mut cards = 0;
+        let mut hands: HashMap<PlayerId, Vec<Card>> = HashMap::new();
+        let mut main_color: Option<CardColor> = None;
+        let mut guesses: HashMap<PlayerId, u32> = HashMap::new();
+        let mut stitches: HashMap<PlayerId, u32> = HashMap::new();
+        let mut trick: Vec<Play> = Vec::new();
+        let mut trick_number = 0;
+        let mut moments: HashMap<PlayerId, Vec<Moment>> = HashMap::new();
+
+        for event in &record.events {
+            match event {
+                Event::RoundStarted {
+                    round: number,
+                    hands: dealt,
+                    main_color: color,
+                    ..
+                } => {
+                    round = *number;
+                    cards = dealt.first().map_or(0, |e| e.1.len() as u32);
+                    hands = dealt.iter().cloned().collect();
+                    main_color = *color;
+                    guesses.clear();
+                    stitches.clear();
+                    trick.clear();
+                    trick_number = 0;
+                    moments.clear();
+                }
+                Event::Guessed {
+                    player,
+                    stitches: guess,
+                } => {
+                    guesses.insert(*player, *guess);
+                }
+                Event::Played { player, card } => {
+                    let hand = hands.entry(*player).or_default();
+                    let cards: Vec<Card> = trick.iter().map(|e| e.card).collect();
+                    let options = Card::playable_cards(hand.clone(), cards);
+                    if let Some(index) = hand.iter().position(|e| e == card) {
+                        hand.remove(index);
+                    }
+                    trick.push(Play {
+                        player: *player,
+                        card: *card,
+                        options,
+                    });
+                }
+                Event::TrickWon { player } => {
+                    trick_number += 1;
+                    *stitches.entry(*player).or_default() += 1;
+
+                    let cards: Vec<Card> = trick.iter().map(|e| e.card).collect();
+                    for (index, play) in trick.iter().enumerate() {
+                        let won = play.player == *player;
+                        let alternatives: Vec<Card> = play
+                            .options
+                            .iter()
+                            .copied()
+                            .filter(|alternative| *alternative != play.card)
+                            .filter(|alternative| {
+                                let mut changed = cards.clone();
+                                changed[index] = *alternative;
+                                (Card::winning_index(changed, main_color) == Ok(index)) != won
+                            })
+                            .collect();
+
+                        if !alternatives.is_empty() {
+                            moments.entry(play.player).or_default().push(Moment {
+                                trick: trick_number,
+                                played: play.card,
+                                alternatives,
+                                would_win: !won,
+                            });
+                        }
+                    }
+                    trick.clear();
+                }
+                Event::GameStarted { .. } | Event::Chat { .. } | Event::Emote { .. } => {}
+                Event::RoundScored { points } => {
+                    for (id, points) in points {
+                        let guess_stitches = guesses.get(id).copied().unwrap_or(0);
+                        let actual_stitches = stitches.get(id).copied().unwrap_or(0);
+
+                        // only the moments that would have helped to get the guessed stitches are relevant
+                        let moments: Vec<Moment> = moments
+                            .remove(id)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|e| {
+                                (e.would_win && actual_stitches < guess_stitches)
+                                    || (!e.would_win && actual_stitches > guess_stitches)
+                            })
+                            .collect();
+
+                        if let Some(player) = players.iter_mut().find(|e| e.id == *id) {
+                            player.score += points;
+                            player.rounds.push(RoundReport {
+                                round,
+                                cards,
+                                guess_stitches,
+                                actual_stitches,
+                                points: *points,
+                                points_lost: Game::points(guess_stitches, guess_stitches) - points,
+                                moments,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Report { players }
+    }
+
+    /// Returns the report as a Markdown document with a table and the relevant moments per player.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", i18n::text("report.title"));
+        let header = i18n::text("report.header");
+        let separator: Vec<String> = header
+            .split('|')
+            .map(|e| match e.chars().count() {
+                0 => String::new(),
+                width => format!("{}:", "-".repeat(width - 1)),
+            })
+            .collect();
+
+        for player in &self.players {
+            out.push_str(&format!(
+                "\n## {}\n\n",
+                i18n::text_with("report.player", &[&player.name, &player.score])
+            ));
+            out.push_str(&format!("{}\n{}\n", header, separator.join("|")));
+            for round in &player.rounds {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    round.round,
+                    round.cards,
+                    round.guess_stitches,
+                    round.actual_stitches,
+                    round.points,
+                    round.points_lost
+                ));
+            }
+
+            let moments: Vec<String> = player
+                .rounds
+                .iter()
+                .flat_map(|round| {
+                    round.moments.iter().map(move |e| {
+                        format!(
+                            "- {}\n",
+                            i18n::text_with(
+                                "report.moment",
+                                &[&round.round, &e.trick, &e.description()]
+                            )
+                        )
+                    })
+                })
+                .collect();
+            if !moments.is_empty() {
+                out.push_str(&format!("\n{}\n\n", i18n::text("report.missed_chances")));
+                out.push_str(&moments.concat());
+            }
+        }
+        out
+    }
+
+    /// Returns the report as pretty printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("A report can always be serialized.")
+    }
+}
@@ -1 +1,285 @@
+use std::collections::BTreeMap;
 
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::error::WizardError;
+use crate::wizard::game::Game;
+use crate::wizard::player::PlayerId;
+
+/// The hands of the players in seating order.
+pub type Hands = Vec<(PlayerId, Vec<Card>)>;
+
+/// Something a player does in a round.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Move {
+    Guess(u32),
+    Play(Card),
+}
+
+/// A move that was made in a round together with everything that is needed to take it back.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Step {
+    pub player: PlayerId,
+    pub action: Move,
+    /// The position of a played card in the hand of the player.
+    pub position: usize,
+    /// The leader of the trick before it was completed by this move, if it was completed.
+    pub previous_leader: Option<PlayerId>,
+}
+
+/// The state of a single round. The hands, guesses and stitches are kept per player, the player left of the dealer
+/// guesses first and leads the first trick. <br>
+/// Every move is a transition from one state to the next and is kept on a stack, so moves can be taken back
+/// in reverse order with `undo`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::card::{Card, CardColor};
+/// # use wizard::wizard::player::PlayerId;
+/// # use wizard::wizard::round::{Move, Round};
+/// let (a, b, c) = (PlayerId(1), PlayerId(2), PlayerId(3));
+/// let hands = vec![
+///     (a, vec![Card::Number(3, CardColor::RED)]),
+///     (b, vec![Card::Wizard]),
+///     (c, vec![Card::Fool]),
+/// ];
+/// // the third player deals, so the first player starts
+/// let mut round = Round::new(1, hands, c, None, None)?;
+/// assert_eq!(round.bidding_order(), vec![a, b, c]);
+///
+/// round.guess(0)?;
+/// assert_eq!(round.next_player(), Some(b));
+/// round.undo();
+/// assert_eq!(round.next_player(), Some(a));
+///
+/// for stitches in [0, 1, 1] {
+///     round.guess(stitches)?;
+/// }
+/// round.play(Card::Number(3, CardColor::RED))?;
+/// round.play(Card::Wizard)?;
+/// assert_eq!(round.play(Card::Fool)?, Some(b));
+/// assert_eq!(round.leader, b);
+/// assert!(round.is_finished());
+///
+/// assert_eq!(round.undo().unwrap().action, Move::Play(Card::Fool));
+/// assert_eq!(round.stitches[&b], 0);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct Round {
+    pub number: u32,
+    /// The amount of cards every player got, which is also the amount of tricks.
+    pub cards: u32,
+    /// The players in the order they sit at the table.
+    pub seats: Vec<PlayerId>,
+    pub hands: BTreeMap<PlayerId, Vec<Card>>,
+    pub dealer: PlayerId,
+    pub trump_card: Option<Card>,
+    pub main_color: Option<CardColor>,
+    /// The guess of every player that already guessed.
+    pub guesses: BTreeMap<PlayerId, u32>,
+    pub stitches: BTreeMap<PlayerId, u32>,
+    /// The cards of all completed tricks.
+    pub played: Vec<Card>,
+    pub trick: Vec<Card>,
+    /// The player that leads the current trick. This is the player left of the dealer for the first trick,
+    /// then the winner of the last trick.
+    pub leader: PlayerId,
+    pub history: Vec<Step>,
+}
+
+impl Round {
+    /// Starts a round with the given hands of every player in seating order. <br>
+    /// `WizardError::InvalidPlayerCount` is returned if there are no hands.
+    pub fn new(
+        number: u32,
+        hands: Hands,
+        dealer: PlayerId,
+        trump_card: Option<Card>,
+        main_color: Option<CardColor>,
+    ) -> Result<Self, WizardError> {
+        if hands.is_empty() {
+            return Err(WizardError::InvalidPlayerCount(0));
+        }
+
+        let seats: Vec<PlayerId> = hands.iter().map(|e| e.0).collect();
+        let dealer_seat = seats.iter().position(|e| *e == dealer).unwrap_or(0);
+        Ok(Round {
+            number,
+            cards: hands.first().map_or(0, |e| e.1.len() as u32),
+            leader: seats[(dealer_seat + 1) % seats.len()],
+            stitches: seats.iter().map(|e| (*e, 0)).collect(),
+            seats,
+            hands: hands.into_iter().collect(),
+            dealer,
+            trump_card,
+            main_color,
+            guesses: BTreeMap::new(),
+            played: Vec::new(),
+            trick: Vec::new(),
+            history: Vec::new(),
+        })
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// Returns the players in seating order, starting with the given player.
+    pub fn seats_from(&self, player: PlayerId) -> Vec<PlayerId> {
+        let start = self.seats.iter().position(|e| *e == player).unwrap_or(0);
+        (0..self.player_count())
+            .map(|i| self.seats[(start + i) % self.player_count()])
+            .collect()
+    }
+
+    /// Returns the players in the order they guess in, starting left of the dealer.
+    pub fn bidding_order(&self) -> Vec<PlayerId> {
+        let mut order = self.seats_from(self.dealer);
+        order.rotate_left(1);
+        order
+    }
+
+    /// Returns the players in the order they play the current trick in, starting with the leader.
+    pub fn playing_order(&self) -> Vec<PlayerId> {
+        self.seats_from(self.leader)
+    }
+
+    /// Returns whether not all players guessed their stitches yet.
+    pub fn is_bidding(&self) -> bool {
+        self.guesses.len() < self.player_count()
+    }
+
+    /// Returns whether all tricks were played.
+    pub fn is_finished(&self) -> bool {
+        self.played.len() == self.cards as usize * self.player_count()
+    }
+
+    /// Returns the player that has to guess or play next, `None` if the round is finished.
+    pub fn next_player(&self) -> Option<PlayerId> {
+        if self.is_bidding() {
+            self.bidding_order()
+                .into_iter()
+                .find(|e| !self.guesses.contains_key(e))
+        } else if self.is_finished() {
+            None
+        } else {
+            Some(self.playing_order()[self.trick.len()])
+        }
+    }
+
+    /// Returns the points of the given player for the round, see `Game::points`.
+    pub fn points(&self, player: PlayerId) -> i32 {
+        Game::points(
+            self.guesses.get(&player).copied().unwrap_or(0),
+            self.stitches.get(&player).copied().unwrap_or(0),
+        )
+    }
+
+    /// Returns the amounts of stitches the next player can guess.
+    pub fn stitch_options(&self) -> Vec<u32> {
+        Game::stitch_options(
+            self.cards,
+            self.guesses.values().sum(),
+            self.guesses.len() + 1 == self.player_count(),
+        )
+    }
+
+    /// Returns the cards the next player is allowed to play.
+    pub fn playable_cards(&self) -> Vec<Card> {
+        match self.next_player() {
+            Some(player) if !self.is_bidding() => {
+                Card::playable_cards(self.hands[&player].clone(), self.trick.clone())
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The next player guesses the given amount of stitches. <br>
+    /// `WizardError::BidOutOfRange` is returned if the amount is not one of the `stitch_options` or the guessing is over.
+    pub fn guess(&mut self, stitches: u32) -> Result<(), WizardError> {
+        let Some(player) = self
+            .next_player()
+            .filter(|_| self.is_bidding() && self.stitch_options().contains(&stitches))
+        else {
+            return Err(WizardError::BidOutOfRange {
+                stitches,
+                options: if self.is_bidding() {
+                    self.stitch_options()
+                } else {
+                    Vec::new()
+                },
+            });
+        };
+
+        self.history.push(Step {
+            player,
+            action: Move::Guess(stitches),
+            position: 0,
+            previous_leader: None,
+        });
+        self.guesses.insert(player, stitches);
+        Ok(())
+    }
+
+    /// The next player plays the given card. If the card completes the trick, the winner of the trick is returned
+    /// and leads the next one. <br>
+    /// `WizardError::IllegalPlay` is returned if the card is not one of the `playable_cards`.
+    pub fn play(&mut self, card: Card) -> Result<Option<PlayerId>, WizardError> {
+        let playable = self.playable_cards();
+        let Some(player) = self.next_player().filter(|_| playable.contains(&card)) else {
+            return Err(WizardError::IllegalPlay { card, playable });
+        };
+
+        let hand = self.hands.get_mut(&player).unwrap();
+        let position = hand.iter().position(|e| *e == card).unwrap();
+        hand.remove(position);
+        self.trick.push(card);
+
+        let mut step = Step {
+            player,
+            action: Move::Play(card),
+            position,
+            previous_leader: None,
+        };
+
+        let mut winner = None;
+        if self.trick.len() == self.player_count() {
+            let index = Card::winning_index(self.trick.clone(), self.main_color)?;
+            step.previous_leader = Some(self.leader);
+            self.leader = self.playing_order()[index];
+            *self.stitches.entry(self.leader).or_insert(0) += 1;
+            self.played.append(&mut self.trick);
+            winner = Some(self.leader);
+        }
+
+        self.history.push(step);
+        Ok(winner)
+    }
+
+    /// Takes back the last move and returns it. `None` is returned if no move was made yet.
+    pub fn undo(&mut self) -> Option<Step> {
+        let step = self.history.pop()?;
+
+        match step.action {
+            Move::Guess(_) => {
+                self.guesses.remove(&step.player);
+            }
+            Move::Play(card) => {
+                if let Some(previous_leader) = step.previous_leader {
+                    *self.stitches.entry(self.leader).or_insert(0) -= 1;
+                    let start = self.played.len() - self.player_count();
+                    self.trick = self.played.split_off(start);
+                    self.leader = previous_leader;
+                }
+                self.trick.pop();
+                self.hands
+                    .entry(step.player)
+                    .or_default()
+                    .insert(step.position, card);
+            }
+        }
+
+        Some(step)
+    }
+}
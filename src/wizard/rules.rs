@@ -0,0 +1,239 @@
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n::{self, Language};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which rounds are played in a game, given as the amount of cards every player gets in each round.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum Schedule {
+    /// All rounds from 1 to 60/player_count cards.
+    Full,
+    /// Only the rounds with an odd amount of cards.
+    Odd,
+    /// The rounds from 1 up to the given amount of cards and back down to 1.
+    UpAndDown(u32),
+    /// The given amount of rounds with a random amount of cards each.
+    Random(u32),
+    /// The given rounds in the given order.
+    List(Vec<u32>),
+}
+
+impl Schedule {
+    /// Returns the amount of cards of every round for a game with the given amount of players. Random round sizes are drawn from `rng`. <br>
+    /// `WizardError::InvalidDealSize` is returned if a round has no cards or more cards than there are for every player,
+    /// `WizardError::InvalidSchedule` if there are no rounds at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::rules::Schedule;
+    /// let mut rng = rand::thread_rng();
+    /// assert_eq!(Schedule::Odd.rounds(6, &mut rng)?, vec![1, 3, 5, 7, 9]);
+    /// assert_eq!(Schedule::UpAndDown(3).rounds(4, &mut rng)?, vec![1, 2, 3, 2, 1]);
+    /// assert!(Schedule::List(vec![1, 21]).rounds(3, &mut rng).is_err());
+    /// # Ok::<(), wizard::wizard::error::WizardError>(())
+    /// ```
+    pub fn rounds(&self, player_count: usize, rng: &mut impl Rng) -> Result<Vec<u32>, WizardError> {
+        let max = 60u32.checked_div(player_count as u32).unwrap_or(0);
+        let rounds: Vec<u32> = match self {
+            Schedule::Full => (1..=max).collect(),
+            Schedule::Odd => (1..=max).step_by(2).collect(),
+            Schedule::UpAndDown(top) => (1..*top).chain((1..=*top).rev()).collect(),
+            Schedule::Random(count) => (0..*count)
+                .map(|_| if max == 0 { 0 } else { rng.gen_range(1..=max) })
+                .collect(),
+            Schedule::List(rounds) => rounds.clone(),
+        };
+
+        if rounds.is_empty() {
+            return Err(WizardError::InvalidSchedule(self.to_string()));
+        }
+        match rounds.iter().find(|e| **e == 0 || **e > max) {
+            Some(amount) => Err(WizardError::InvalidDealSize {
+                amount: *amount,
+                max,
+            }),
+            None => Ok(rounds),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Schedule::Full => write!(f, "full"),
+            Schedule::Odd => write!(f, "odd"),
+            Schedule::UpAndDown(top) => write!(f, "updown:{}", top),
+            Schedule::Random(count) => write!(f, "random:{}", count),
+            Schedule::List(rounds) => {
+                let rounds: Vec<String> = rounds.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", rounds.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = WizardError;
+
+    /// Reads a schedule as written by `Display`: `full`, `odd`, `updown:N`, `random:N` or a list such as `1,3,5,7,10`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WizardError::InvalidSchedule(s.to_string());
+        let number = |e: &str| e.trim().parse::<u32>().map_err(|_| invalid());
+
+        match s.trim() {
+            "full" => Ok(Schedule::Full),
+            "odd" => Ok(Schedule::Odd),
+            trimmed => match trimmed.split_once(':') {
+                Some(("updown", top)) => Ok(Schedule::UpAndDown(number(top)?)),
+                Some(("random", count)) => Ok(Schedule::Random(number(count)?)),
+                Some(_) => Err(invalid()),
+                None => Ok(Schedule::List(
+                    trimmed.split(',').map(number).collect::<Result<_, _>>()?,
+                )),
+            },
+        }
+    }
+}
+
+/// A rule of the game. The topic is one of deck, dealing, trump, bidding, following, winning, scoring and rounds.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Rule {
+    pub topic: String,
+    pub text: String,
+}
+
+/// The rules of a rule set as they are written by `RuleSet::to_json`.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct RulesReference {
+    pub name: String,
+    pub schedule: Schedule,
+    pub rules: Vec<Rule>,
+}
+
+/// A named set of rules a game can be played with.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub name: String,
+    pub schedule: Schedule,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            name: i18n::text("rules.standard"),
+            schedule: Schedule::Full,
+        }
+    }
+}
+
+/// The keys of the names of the presets with their round schedules.
+fn preset_schedules() -> Vec<(&'static str, Schedule)> {
+    vec![
+        ("rules.standard", Schedule::Full),
+        ("rules.short", Schedule::Odd),
+        ("rules.pyramid", Schedule::UpAndDown(5)),
+        ("rules.lunch", Schedule::Random(5)),
+    ]
+}
+
+impl RuleSet {
+    /// Returns the predefined rule sets: the standard game and shorter games for a lunch break.
+    pub fn presets() -> Vec<RuleSet> {
+        preset_schedules()
+            .into_iter()
+            .map(|(key, schedule)| RuleSet {
+                name: i18n::text(key),
+                schedule,
+            })
+            .collect()
+    }
+
+    /// Returns the preset with the given name in the current language or in English, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::rules::{RuleSet, Schedule};
+    /// assert_eq!(RuleSet::preset("short").unwrap().schedule, Schedule::Odd);
+    /// assert_eq!(RuleSet::preset("chess"), None);
+    /// ```
+    pub fn preset(name: &str) -> Option<RuleSet> {
+        preset_schedules()
+            .into_iter()
+            .find(|(key, _)| {
+                [i18n::text(key), i18n::text_in(Language::English, key, &[])]
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(name.trim()))
+            })
+            .map(|(key, schedule)| RuleSet {
+                name: i18n::text(key),
+                schedule,
+            })
+    }
+
+    /// Returns a rule set with its own round schedule.
+    pub fn custom(schedule: Schedule) -> RuleSet {
+        RuleSet {
+            name: i18n::text("rules.custom"),
+            schedule,
+        }
+    }
+
+    /// Returns the rules of the game, each with the topic it's about.
+    pub fn rules(&self) -> Vec<Rule> {
+        let topics = [
+            "deck",
+            "dealing",
+            "trump",
+            "bidding",
+            "following",
+            "winning",
+            "scoring",
+            "rounds",
+        ];
+        topics
+            .into_iter()
+            .map(|topic| Rule {
+                topic: topic.to_string(),
+                text: i18n::text_with(&format!("rule.{}", topic), &[&self.schedule]),
+            })
+            .collect()
+    }
+
+    /// Returns the rules of the game with this rule set as printable text.
+    pub fn summary(&self) -> String {
+        let mut out = format!("Wizard: {}\n\n", self.description());
+        for rule in self.rules() {
+            out.push_str(&format!("- {}\n", rule.text));
+        }
+        out
+    }
+
+    /// Returns the rules of the game with this rule set as JSON for other programs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::rules::{RuleSet, RulesReference, Schedule};
+    /// let json = RuleSet::preset("pyramid").unwrap().to_json();
+    /// let reference: RulesReference = serde_json::from_str(&json).unwrap();
+    /// assert_eq!(reference.schedule, Schedule::UpAndDown(5));
+    /// assert!(reference.rules.iter().any(|e| e.topic == "winning"));
+    /// ```
+    pub fn to_json(&self) -> String {
+        let reference = RulesReference {
+            name: self.name.clone(),
+            schedule: self.schedule.clone(),
+            rules: self.rules(),
+        };
+        serde_json::to_string_pretty(&reference).expect("The rules can always be serialized.")
+    }
+
+    /// Returns the name and the round schedule, e.g. `"Short (odd)"`.
+    pub fn description(&self) -> String {
+        format!("{} ({})", self.name, self.schedule)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::wizard::duplicate::DealSet;
+use crate::wizard::error::WizardError;
+use crate::wizard::external;
+use crate::wizard::game::Game;
+use crate::wizard::player::Player;
+use crate::wizard::record::GameRecord;
+use crate::wizard::rules::RuleSet;
+
+/// The results of a player over all games of a simulation.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SimulationResult {
+    pub name: String,
+    pub games: u32,
+    /// Games with the highest score. Shared wins count as a fraction.
+    pub wins: f64,
+    pub total_score: i64,
+}
+
+impl SimulationResult {
+    pub fn average_score(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.total_score as f64 / self.games as f64
+        }
+    }
+}
+
+/// Plays many games between computer controlled players without asking anyone. <br>
+/// The cards of game `i` are dealt from the deal set with the seed `seed + i`, so a simulation can be repeated.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::player::Player;
+/// # use wizard::wizard::rules::{RuleSet, Schedule};
+/// # use wizard::wizard::simulation::Simulation;
+/// let simulation = Simulation {
+///     players: (1..=3).map(|i| Player::bot(Difficulty::Hard, i)).collect(),
+///     rules: RuleSet { name: "Quick".to_string(), schedule: Schedule::List(vec![1, 2]) },
+///     seed: 7,
+///     games: 4,
+/// };
+/// let records = simulation.run()?;
+/// assert_eq!(records.len(), 4);
+/// assert_eq!(simulation.results(&records)[0].games, 4);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct Simulation {
+    /// The players in seating order. Only built-in and external bots should take part.
+    pub players: Vec<Player>,
+    pub rules: RuleSet,
+    pub seed: u64,
+    pub games: u32,
+}
+
+impl Simulation {
+    /// Plays all games without printing anything and returns their records. <br>
+    /// `WizardError::InvalidPlayerCount` is returned if there are not 3 to 6 players.
+    pub fn run(&self) -> Result<Vec<GameRecord>, WizardError> {
+        let mut records = Vec::new();
+        for i in 0..self.games {
+            let seed = self.seed.wrapping_add(i as u64);
+            let mut game = Game {
+                players: self.players.clone(),
+                deal_set: Some(DealSet::generate(
+                    seed,
+                    self.players.len() as u32,
+                    &self.rules.schedule,
+                )?),
+                rules: self.rules.clone(),
+                ..Game::default()
+            };
+            let mut bots = external::spawn_all(&game)?;
+            external::play(&mut game, &mut bots)?;
+            records.push(game.record);
+        }
+        Ok(records)
+    }
+
+    /// Returns the results of every player in the given records, sorted by the average score.
+    pub fn results(&self, records: &[GameRecord]) -> Vec<SimulationResult> {
+        let mut results: Vec<SimulationResult> = self
+            .players
+            .iter()
+            .map(|e| SimulationResult {
+                name: e.name.clone(),
+                games: 0,
+                wins: 0.0,
+                total_score: 0,
+            })
+            .collect();
+
+        for record in records {
+            let scores = record.scores();
+            let best = scores.values().max().copied();
+            let winners = scores.values().filter(|e| Some(**e) == best).count() as f64;
+            for (player, result) in self.players.iter().zip(&mut results) {
+                if let Some(score) = scores.get(&player.id) {
+                    result.games += 1;
+                    result.total_score += *score as i64;
+                    if Some(*score) == best {
+                        result.wins += 1.0 / winners;
+                    }
+                }
+            }
+        }
+
+        results.sort_by(|a, b| b.average_score().total_cmp(&a.average_score()));
+        results
+    }
+
+    /// Returns the results as printable text.
+    pub fn results_text(results: &[SimulationResult]) -> String {
+        let mut out = format!(
+            "{:<16} {:>5} {:>7} {:>9}\n",
+            "Name", "Games", "Wins", "Avg score"
+        );
+        for result in results {
+            out.push_str(&format!(
+                "{:<16} {:>5} {:>7.1} {:>9.1}\n",
+                result.name,
+                result.games,
+                result.wins,
+                result.average_score()
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,447 @@
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::player::PlayerId;
+use crate::wizard::round::Round;
+use std::collections::HashMap;
+
+/// What the player that is solved for tries to do, while all other players try the opposite.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Goal {
+    MostTricks,
+    FewestTricks,
+}
+
+/// The range of tricks a player can force in a round, when all hands are known and everybody plays perfectly.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Solution {
+    pub player: PlayerId,
+    /// The most tricks the player takes against all other players trying to keep the count low.
+    pub max_tricks: u32,
+    /// The fewest tricks the player takes against all other players trying to push the count up.
+    pub min_tricks: u32,
+    /// The remaining plays of an optimal game for `max_tricks`.
+    pub max_line: Vec<(PlayerId, Card)>,
+    /// The remaining plays of an optimal game for `min_tricks`.
+    pub min_line: Vec<(PlayerId, Card)>,
+}
+
+/// A double-dummy solver for a single round: every player sees all hands. <br>
+/// The tricks of one player are searched with alpha-beta pruning, the bounds of every position are memoized for all
+/// null-window searches and cards that can't make a difference, such as two Wizards or the Red 7 and 8 when nobody
+/// else holds Red, are only tried once. <br>
+/// The time grows quickly with the amount of cards in play, see `Solver::max_cards`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::card::{Card, CardColor};
+/// # use wizard::wizard::player::PlayerId;
+/// # use wizard::wizard::round::Round;
+/// # use wizard::wizard::solver::{Goal, Solver};
+/// let (a, b, c) = (PlayerId(1), PlayerId(2), PlayerId(3));
+/// let hands = vec![
+///     (a, vec![Card::Number(13, CardColor::RED), Card::Number(2, CardColor::BLUE)]),
+///     (b, vec![Card::Number(5, CardColor::RED), Card::Number(9, CardColor::BLUE)]),
+///     (c, vec![Card::Wizard, Card::Fool]),
+/// ];
+/// // the third player deals, so the first player leads
+/// let round = Round::new(2, hands, c, None, None)?;
+/// let mut solver = Solver::new(&round);
+///
+/// // the Wizard always takes a trick, the Fool never does
+/// let (tricks, line) = solver.solve(c, Goal::MostTricks)?;
+/// assert_eq!(tricks, 1);
+/// assert_eq!(line.len(), 6);
+/// assert_eq!(solver.solve(c, Goal::FewestTricks)?.0, 1);
+///
+/// // the Wizard beats the Red 13 when the first player needs the trick and lets it win otherwise
+/// let solution = solver.analyse(a)?;
+/// assert_eq!((solution.max_tricks, solution.min_tricks), (0, 1));
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Solver {
+    seats: Vec<PlayerId>,
+    /// Every card of the round that is in a hand or in the current trick.
+    cards: Vec<Card>,
+    /// The seat that holds or played every card.
+    owners: Vec<usize>,
+    /// The cards sorted by kind, color and value, see `Solver::order`.
+    sorted: Vec<usize>,
+    /// The position of every card in `sorted`.
+    positions: Vec<usize>,
+    /// The cards of every seat as bits of `cards`.
+    hands: Vec<u64>,
+    main_color: Option<CardColor>,
+    leader: usize,
+    trick: Vec<usize>,
+    /// The tricks every seat already won.
+    taken: Vec<u32>,
+    target: usize,
+    goal: Goal,
+    /// The lower and upper bound of the tricks of the target for every position that was searched, see `Solver::key`.
+    table: HashMap<[u64; 5], (i32, i32)>,
+    /// The positions that were searched so far.
+    pub nodes: u64,
+}
+
+impl Solver {
+    /// Prepares the solver for the current position of the round, which may be in the middle of a trick.
+    pub fn new(round: &Round) -> Self {
+        let seats = round.seats.clone();
+        let leader = seats.iter().position(|e| *e == round.leader).unwrap_or(0);
+        let mut cards = Vec::new();
+        let mut owners = Vec::new();
+        let mut hands = vec![0; seats.len()];
+        for (seat, player) in seats.iter().enumerate() {
+            for card in round.hands.get(player).into_iter().flatten() {
+                hands[seat] |= 1 << cards.len();
+                cards.push(*card);
+                owners.push(seat);
+            }
+        }
+        let mut trick = Vec::new();
+        for (position, card) in round.trick.iter().enumerate() {
+            trick.push(cards.len());
+            cards.push(*card);
+            owners.push((leader + position) % seats.len());
+        }
+        let mut sorted: Vec<usize> = (0..cards.len()).collect();
+        sorted.sort_by_key(|e| Solver::order(cards[*e]));
+        let mut positions = vec![0; cards.len()];
+        for (position, e) in sorted.iter().enumerate() {
+            positions[*e] = position;
+        }
+
+        Solver {
+            leader,
+            taken: seats
+                .iter()
+                .map(|e| round.stitches.get(e).copied().unwrap_or(0))
+                .collect(),
+            seats,
+            cards,
+            owners,
+            sorted,
+            positions,
+            hands,
+            main_color: round.main_color,
+            trick,
+            target: 0,
+            goal: Goal::MostTricks,
+            table: HashMap::new(),
+            nodes: 0,
+        }
+    }
+
+    /// Returns the most cards per player of the rounds that are solved within about a second, 8 for 3 players and
+    /// 4 for 6 players.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::solver::Solver;
+    /// assert_eq!(Solver::max_cards(3), 8);
+    /// assert_eq!(Solver::max_cards(4), 6);
+    /// assert_eq!(Solver::max_cards(6), 4);
+    /// ```
+    pub fn max_cards(player_count: usize) -> u32 {
+        24 / player_count.max(1) as u32
+    }
+
+    /// Returns the tricks the player ends the round with when the player plays for the goal and everybody else
+    /// against it, counting the tricks that were already won, together with the remaining plays of such a game. <br>
+    /// `WizardError::PlayerNotSeated` is returned if the player is not part of the round.
+    pub fn solve(
+        &mut self,
+        player: PlayerId,
+        goal: Goal,
+    ) -> Result<(u32, Vec<(PlayerId, Card)>), WizardError> {
+        let target = self
+            .seats
+            .iter()
+            .position(|e| *e == player)
+            .ok_or_else(|| {
+                WizardError::PlayerNotSeated(i18n::text_with("player.unknown", &[&player.0]))
+            })?;
+        if self.target != target || self.goal != goal {
+            self.table.clear();
+        }
+        self.target = target;
+        self.goal = goal;
+
+        let mask = self.hands.iter().fold(0, |mask, e| mask | e);
+        let tricks = self.value(mask, self.leader, &mut self.trick.clone());
+        let line = self.line(mask, tricks);
+        Ok((self.taken[target] + tricks as u32, line))
+    }
+
+    /// Returns the most and fewest tricks the player can force. <br>
+    /// `WizardError::PlayerNotSeated` is returned if the player is not part of the round.
+    pub fn analyse(&mut self, player: PlayerId) -> Result<Solution, WizardError> {
+        let (max_tricks, max_line) = self.solve(player, Goal::MostTricks)?;
+        let (min_tricks, min_line) = self.solve(player, Goal::FewestTricks)?;
+        Ok(Solution {
+            player,
+            max_tricks,
+            min_tricks,
+            max_line,
+            min_line,
+        })
+    }
+
+    /// Returns the solutions of all players in seating order.
+    pub fn analyse_all(&mut self) -> Vec<Solution> {
+        self.seats
+            .clone()
+            .into_iter()
+            .filter_map(|e| self.analyse(e).ok())
+            .collect()
+    }
+
+    /// Returns the exact tricks the target takes from the given position on. Null-window searches that tell whether
+    /// the target takes at least a certain amount of tricks narrow the value down.
+    fn value(&mut self, mask: u64, leader: usize, trick: &mut Vec<usize>) -> i32 {
+        let seat = (leader + trick.len()) % self.seats.len();
+        let remaining = (mask & self.hands[seat]).count_ones() as i32;
+        let key = self.key(mask, leader, trick);
+        let (mut lower, mut upper) = self.table.get(&key).copied().unwrap_or((0, remaining));
+        while lower < upper {
+            let guess = (lower + upper + 1) / 2;
+            let tricks = self.search(mask, leader, trick, guess - 1, guess);
+            if tricks >= guess {
+                lower = tricks;
+            } else {
+                upper = tricks;
+            }
+        }
+        lower
+    }
+
+    /// Returns the tricks the target takes from the given position on with perfect play. The result is exact if it
+    /// lies between `alpha` and `beta`, otherwise it is only a bound on the side it lies on.
+    fn search(
+        &mut self,
+        mask: u64,
+        leader: usize,
+        trick: &mut Vec<usize>,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        let count = self.seats.len();
+        if trick.len() == count {
+            let cards: Vec<Card> = trick.iter().map(|e| self.cards[*e]).collect();
+            let index = Card::winning_index(cards, self.main_color).unwrap_or(0);
+            let winner = (leader + index) % count;
+            let won = i32::from(winner == self.target);
+            let finished = std::mem::take(trick);
+            let tricks = won + self.search(mask, winner, trick, alpha - won, beta - won);
+            *trick = finished;
+            return tricks;
+        }
+
+        // the next player still holds a card for every remaining trick
+        let seat = (leader + trick.len()) % count;
+        let remaining = (mask & self.hands[seat]).count_ones() as i32;
+        if remaining == 0 {
+            return 0;
+        }
+        let key = self.key(mask, leader, trick);
+        let (mut lower, mut upper) = self.table.get(&key).copied().unwrap_or((0, remaining));
+        if lower == upper || lower >= beta {
+            return lower;
+        }
+        if upper <= alpha {
+            return upper;
+        }
+        let (alpha, beta) = (alpha.max(lower), beta.min(upper));
+
+        let maximizing = (seat == self.target) == (self.goal == Goal::MostTricks);
+        let (mut low, mut high) = (alpha, beta);
+        let mut best = if maximizing { i32::MIN } else { i32::MAX };
+        for index in self.moves(mask, seat, trick) {
+            trick.push(index);
+            let tricks = self.search(mask & !(1 << index), leader, trick, low, high);
+            trick.pop();
+            if maximizing {
+                best = best.max(tricks);
+                low = low.max(tricks);
+            } else {
+                best = best.min(tricks);
+                high = high.min(tricks);
+            }
+            if low >= high {
+                break;
+            }
+        }
+
+        if best <= alpha {
+            upper = best;
+        } else if best >= beta {
+            lower = best;
+        } else {
+            (lower, upper) = (best, best);
+        }
+        self.table.insert(key, (lower, upper));
+        best
+    }
+
+    /// Returns the playable cards of the seat, one card of every group of equivalent cards, the most promising first.
+    fn moves(&self, mask: u64, seat: usize, trick: &[usize]) -> Vec<usize> {
+        let hand = mask & self.hands[seat];
+        let mut moves: Vec<usize> = self
+            .sorted
+            .iter()
+            .copied()
+            .filter(|e| hand & (1 << e) != 0)
+            .collect();
+        let cards: Vec<Card> = trick.iter().map(|e| self.cards[*e]).collect();
+        if let Some(color) = Card::get_prio_color(cards.clone()) {
+            let follows =
+                |e: &usize| matches!(self.cards[*e], Card::Number(_, other) if other == color);
+            if moves.iter().any(follows) {
+                moves.retain(|e| follows(e) || !matches!(self.cards[*e], Card::Number(_, _)));
+            }
+        }
+
+        // cards of the same color are equivalent if no other card still in play lies between them
+        moves.dedup_by(
+            |later, first| match (self.cards[*first], self.cards[*later]) {
+                (Card::Number(_, color), Card::Number(_, other)) if color == other => self.sorted
+                    [self.positions[*first] + 1..self.positions[*later]]
+                    .iter()
+                    .all(|e| mask & (1 << e) == 0 && !trick.contains(e)),
+                (first, later) => first == later,
+            },
+        );
+
+        let main_color = self.main_color;
+        let strength = |card: Card| match card {
+            Card::Fool => 0,
+            Card::Number(value, color) if Some(color) == main_color => value + 13,
+            Card::Number(value, _) => value,
+            Card::Wizard => 27,
+        };
+        // high cards are led when the target wants tricks, low cards otherwise
+        if trick.is_empty() {
+            if self.goal == Goal::FewestTricks {
+                moves.sort_by_key(|e| strength(self.cards[*e]));
+            } else {
+                moves.sort_by_key(|e| std::cmp::Reverse(strength(self.cards[*e])));
+            }
+            return moves;
+        }
+
+        // the cards that give the trick to the side the player wants to have it come first, the cheapest of them first,
+        // only when the target avoids tricks, high cards are gotten rid of as long as they don't win
+        let count = self.seats.len();
+        let leader = (seat + count - trick.len()) % count;
+        let wanted = (seat == self.target) == (self.goal == Goal::MostTricks);
+        moves.sort_by_cached_key(|e| {
+            let mut cards = cards.clone();
+            cards.push(self.cards[*e]);
+            let index = Card::winning_index(cards, main_color).unwrap_or(0);
+            let good = ((leader + index) % count == self.target) == wanted;
+            let strength = strength(self.cards[*e]) as i32;
+            let takes = index == trick.len();
+            (
+                !good,
+                if good && !takes && self.goal == Goal::FewestTricks {
+                    -strength
+                } else {
+                    strength
+                },
+            )
+        });
+        moves
+    }
+
+    /// Returns a key for the position that only depends on the order of the remaining cards within every color,
+    /// as the cards that were already played can't make a difference anymore. Every card is noted in 4 bits as its
+    /// owner or, for cards in the trick, as its position in the trick. The last word holds the leader and the amount
+    /// of remaining cards of every kind and color, which tells where the colors start.
+    fn key(&self, mask: u64, leader: usize, trick: &[usize]) -> [u64; 5] {
+        let mut key = [0; 5];
+        let mut bits = 0;
+        let mut counts = leader as u64;
+        for e in &self.sorted {
+            let code = if mask & (1 << e) != 0 {
+                self.owners[*e]
+            } else if let Some(position) = trick.iter().position(|card| card == e) {
+                self.seats.len() + position
+            } else {
+                continue;
+            };
+            key[bits / 64] |= (code as u64) << (bits % 64);
+            bits += 4;
+            let group = match Solver::order(self.cards[*e]) {
+                (0, _, _) => 0,
+                (1, color, _) => color + 1,
+                _ => 5,
+            };
+            counts += 1 << (4 + group * 4);
+        }
+        key[4] = counts;
+        key
+    }
+
+    /// Sorts the cards so that equivalent cards are next to each other.
+    fn order(card: Card) -> (u32, usize, u32) {
+        match card {
+            Card::Fool => (0, 0, 0),
+            Card::Number(value, color) => (
+                1,
+                CardColor::all_colors()
+                    .iter()
+                    .position(|e| *e == color)
+                    .unwrap_or(0),
+                value,
+            ),
+            Card::Wizard => (2, 0, 0),
+        }
+    }
+
+    /// Returns the remaining plays of an optimal game from the current position on, which is worth the given tricks.
+    fn line(&mut self, mut mask: u64, mut tricks: i32) -> Vec<(PlayerId, Card)> {
+        let count = self.seats.len();
+        let mut leader = self.leader;
+        let mut trick = self.trick.clone();
+        let mut line = Vec::new();
+        while mask != 0 {
+            let seat = (leader + trick.len()) % count;
+            let maximizing = (seat == self.target) == (self.goal == Goal::MostTricks);
+            let moves = self.moves(mask, seat, &trick);
+            let mut chosen = moves[0];
+            for index in moves {
+                // no move is better than the value of the position, so it's enough to check the other side
+                trick.push(index);
+                let after = if maximizing {
+                    self.search(mask & !(1 << index), leader, &mut trick, tricks - 1, tricks)
+                        >= tricks
+                } else {
+                    self.search(mask & !(1 << index), leader, &mut trick, tricks, tricks + 1)
+                        <= tricks
+                };
+                trick.pop();
+                if after {
+                    chosen = index;
+                    break;
+                }
+            }
+            trick.push(chosen);
+            mask &= !(1 << chosen);
+            line.push((self.seats[seat], self.cards[chosen]));
+
+            if trick.len() == count {
+                let cards: Vec<Card> = trick.iter().map(|e| self.cards[*e]).collect();
+                leader =
+                    (leader + Card::winning_index(cards, self.main_color).unwrap_or(0)) % count;
+                tricks -= i32::from(leader == self.target);
+                trick.clear();
+            }
+        }
+        line
+    }
+}
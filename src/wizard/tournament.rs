@@ -0,0 +1,254 @@
+use crate::wizard::error::WizardError;
+use crate::wizard::game::Game;
+use crate::wizard::i18n;
+use crate::wizard::player::Player;
+use crate::wizard::record::GameRecord;
+use crate::wizard::rules::RuleSet;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How the results of the tables are turned into tournament points.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Scoring {
+    /// The score of a player minus the average score of the table.
+    NormalizedScore,
+    /// The share of opponents at the table the player beat, ties count half. A win is worth 1 point.
+    Placement,
+}
+
+impl Scoring {
+    /// Returns the *name* of the given scoring as a String.
+    pub fn name(&self) -> String {
+        match self {
+            Scoring::NormalizedScore => i18n::text("scoring.normalized_score"),
+            Scoring::Placement => i18n::text("scoring.placement"),
+        }
+    }
+
+    /// Returns a `Vec<Scoring>` with all scorings.
+    pub fn all_scorings() -> Vec<Scoring> {
+        vec![Scoring::NormalizedScore, Scoring::Placement]
+    }
+
+    /// Returns the tournament points of every player for the final scores of a table.
+    pub fn points(&self, scores: &[i32]) -> Vec<f64> {
+        match self {
+            Scoring::NormalizedScore => {
+                let average = scores.iter().sum::<i32>() as f64 / scores.len() as f64;
+                scores.iter().map(|e| *e as f64 - average).collect()
+            }
+            Scoring::Placement => scores
+                .iter()
+                .map(|score| {
+                    let beaten = scores.iter().filter(|e| *e < score).count() as f64;
+                    // the player itself is counted as a tie
+                    let tied = scores.iter().filter(|e| *e == score).count() as f64 - 1.0;
+                    (beaten + tied / 2.0) / (scores.len() - 1) as f64
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The accumulated results of a player in a tournament.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub points: f64,
+    pub games: u32,
+    pub total_score: i32,
+}
+
+/// A tournament over several sessions. In every session the roster is split into tables of 3 to 6 players,
+/// each table plays a whole game. The seating is chosen so that players meet different opponents.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::player::Player;
+/// # use wizard::wizard::tournament::{Scoring, Tournament};
+/// let roster = (1..=7).map(|i| Player::bot(Difficulty::Medium, i)).collect();
+/// let tournament = Tournament::new(roster, Scoring::Placement)?;
+///
+/// assert_eq!(Tournament::table_sizes(7), vec![4, 3]);
+/// let seating = tournament.next_seating(&mut rand::thread_rng());
+/// assert_eq!(seating.len(), 2);
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+pub struct Tournament {
+    pub roster: Vec<Player>,
+    pub scoring: Scoring,
+    /// The rules every table plays with.
+    pub rules: RuleSet,
+    /// The tables of every session that was played, as indices into the roster.
+    pub sessions: Vec<Vec<Vec<usize>>>,
+    /// The final scores of every game that was played, as indices into the roster.
+    pub results: Vec<Vec<(usize, i32)>>,
+}
+
+impl Tournament {
+    /// Creates a tournament for the given roster. <br>
+    /// `WizardError::InvalidPlayerCount` is returned if there are less than 3 players in the roster.
+    pub fn new(roster: Vec<Player>, scoring: Scoring) -> Result<Self, WizardError> {
+        if roster.len() < 3 {
+            return Err(WizardError::InvalidPlayerCount(roster.len()));
+        }
+
+        Ok(Tournament {
+            roster,
+            scoring,
+            rules: RuleSet::default(),
+            sessions: Vec::new(),
+            results: Vec::new(),
+        })
+    }
+
+    /// Returns the sizes of the tables for the given amount of players. There are as few tables as possible,
+    /// each with 3 to 6 players and the sizes differ by at most one.
+    pub fn table_sizes(player_count: usize) -> Vec<usize> {
+        let tables = player_count.div_ceil(6);
+        (0..tables)
+            .map(|i| player_count / tables + (i < player_count % tables) as usize)
+            .collect()
+    }
+
+    /// Returns how often two players of the roster sat at the same table.
+    pub fn meetings(&self, a: usize, b: usize) -> u32 {
+        self.sessions
+            .iter()
+            .flatten()
+            .filter(|table| table.contains(&a) && table.contains(&b))
+            .count() as u32
+    }
+
+    /// Returns the seating for the next session. Several random seatings are tried and the one
+    /// with the fewest repeated meetings of opponents is used.
+    pub fn next_seating<R: Rng>(&self, rng: &mut R) -> Vec<Vec<usize>> {
+        let sizes = Tournament::table_sizes(self.roster.len());
+        let mut best: Option<(u32, Vec<Vec<usize>>)> = None;
+
+        for _ in 0..200 {
+            let mut order: Vec<usize> = (0..self.roster.len()).collect();
+            order.shuffle(rng);
+
+            // every player takes the free seat with the fewest known opponents
+            let mut tables: Vec<Vec<usize>> = vec![Vec::new(); sizes.len()];
+            let mut repeats = 0;
+            for player in order {
+                let (table, cost) = tables
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, table)| table.len() < sizes[*i])
+                    .map(|(i, table)| {
+                        let cost: u32 = table.iter().map(|e| self.meetings(player, *e)).sum();
+                        (i, cost)
+                    })
+                    .min_by_key(|(_, cost)| *cost)
+                    .unwrap();
+                tables[table].push(player);
+                repeats += cost;
+            }
+
+            if best.as_ref().is_none_or(|(e, _)| repeats < *e) {
+                best = Some((repeats, tables));
+            }
+        }
+
+        best.unwrap().1
+    }
+
+    /// Plays the given seating as the next session. Every table plays a whole game, the records of the games are returned.
+    pub fn play_session(
+        &mut self,
+        seating: Vec<Vec<usize>>,
+    ) -> Result<Vec<GameRecord>, WizardError> {
+        let mut records = Vec::new();
+
+        for table in &seating {
+            let mut game = Game {
+                players: table.iter().map(|e| self.roster[*e].clone()).collect(),
+                rules: self.rules.clone(),
+                ..Game::default()
+            };
+            game.play()?;
+
+            self.results.push(
+                table
+                    .iter()
+                    .map(|e| (*e, game.score(self.roster[*e].id)))
+                    .collect(),
+            );
+            records.push(game.record);
+        }
+
+        self.sessions.push(seating);
+        Ok(records)
+    }
+
+    /// Returns the standings of all players, sorted by tournament points.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .roster
+            .iter()
+            .map(|e| Standing {
+                name: e.name.clone(),
+                points: 0.0,
+                games: 0,
+                total_score: 0,
+            })
+            .collect();
+
+        for result in &self.results {
+            let scores: Vec<i32> = result.iter().map(|e| e.1).collect();
+            for ((player, score), points) in result.iter().zip(self.scoring.points(&scores)) {
+                let standing = &mut standings[*player];
+                standing.points += points;
+                standing.games += 1;
+                standing.total_score += score;
+            }
+        }
+
+        standings.sort_by(|a, b| b.points.total_cmp(&a.points));
+        standings
+    }
+
+    /// Returns the tables of the given seating as printable text.
+    pub fn pairings_text(&self, seating: &[Vec<usize>]) -> String {
+        let mut out = String::new();
+        for (i, table) in seating.iter().enumerate() {
+            let names: Vec<&str> = table
+                .iter()
+                .map(|e| self.roster[*e].name.as_str())
+                .collect();
+            out.push_str(&format!(
+                "{}\n",
+                i18n::text_with("tournament.table", &[&(i + 1), &names.join(", ")])
+            ));
+        }
+        out
+    }
+
+    /// Returns the standings as printable text.
+    pub fn standings_text(&self) -> String {
+        let mut out = format!(
+            "{:>3}  {:<16} {:>8} {:>5} {:>11}\n",
+            "#",
+            i18n::text("table.name"),
+            i18n::text("tournament.points"),
+            i18n::text("table.games"),
+            i18n::text("tournament.total_score")
+        );
+        for (place, standing) in self.standings().iter().enumerate() {
+            out.push_str(&format!(
+                "{:>3}  {:<16} {:>8.2} {:>5} {:>11}\n",
+                place + 1,
+                standing.name,
+                standing.points,
+                standing.games,
+                standing.total_score
+            ));
+        }
+        out
+    }
+}
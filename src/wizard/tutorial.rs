@@ -0,0 +1,333 @@
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::error::WizardError;
+use crate::wizard::i18n;
+use crate::wizard::player::PlayerId;
+use crate::wizard::round::Round;
+
+/// The player the learner plays. The learner sits between Anna, who leads, and Ben.
+pub const LEARNER: PlayerId = PlayerId(2);
+
+/// What the learner has to do in a lesson.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Task {
+    WinTrick,
+    LoseTrick,
+    /// Guess the given amount of tricks.
+    Guess(u32),
+}
+
+/// Whether the learner solved the task, and why.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Feedback {
+    pub passed: bool,
+    pub text: String,
+}
+
+/// A step of the tutorial: a scripted round in which the learner has to guess or play a card.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Lesson {
+    pub title: String,
+    /// The rule the lesson teaches, shown before the task.
+    pub text: String,
+    pub task: Task,
+    /// The round with the scripted hands, where it's the learner's turn.
+    pub round: Round,
+    /// The cards Ben plays after the learner.
+    pub replies: Vec<Card>,
+    /// Why the expected guess is right, for guess tasks.
+    pub explanation: String,
+}
+
+impl Lesson {
+    /// Returns the lessons in the order they are taught: following suit, trumps, Wizards, Fools and bidding.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use wizard::wizard::card::{Card, CardColor};
+    /// # use wizard::wizard::tutorial::{Lesson, Task};
+    /// let lessons = Lesson::all_lessons();
+    /// let following = &lessons[0];
+    /// assert_eq!(following.task, Task::LoseTrick);
+    ///
+    /// let feedback = following.try_card(Card::Number(13, CardColor::RED));
+    /// assert!(!feedback.passed);
+    /// assert!(following.try_card(Card::Number(3, CardColor::BLUE)).passed);
+    /// ```
+    pub fn all_lessons() -> Vec<Lesson> {
+        let (red, yellow, green, blue) = (
+            CardColor::RED,
+            CardColor::YELLOW,
+            CardColor::GREEN,
+            CardColor::BLUE,
+        );
+        vec![
+            Lesson::play(
+                "following",
+                Task::LoseTrick,
+                [
+                    vec![
+                        Card::Number(5, blue),
+                        Card::Number(8, red),
+                        Card::Number(2, yellow),
+                    ],
+                    vec![
+                        Card::Number(3, blue),
+                        Card::Number(13, red),
+                        Card::Number(9, green),
+                    ],
+                    vec![
+                        Card::Number(11, blue),
+                        Card::Number(4, green),
+                        Card::Number(6, yellow),
+                    ],
+                ],
+                None,
+                Card::Number(11, blue),
+            ),
+            Lesson::play(
+                "trumps",
+                Task::WinTrick,
+                [
+                    vec![
+                        Card::Number(11, green),
+                        Card::Number(4, blue),
+                        Card::Number(7, yellow),
+                    ],
+                    vec![
+                        Card::Number(2, red),
+                        Card::Number(12, yellow),
+                        Card::Number(7, blue),
+                    ],
+                    vec![
+                        Card::Number(13, green),
+                        Card::Number(1, blue),
+                        Card::Number(9, red),
+                    ],
+                ],
+                Some(Card::Number(5, red)),
+                Card::Number(13, green),
+            ),
+            Lesson::play(
+                "wizards",
+                Task::WinTrick,
+                [
+                    vec![
+                        Card::Number(13, red),
+                        Card::Number(3, green),
+                        Card::Number(6, blue),
+                    ],
+                    vec![Card::Number(4, red), Card::Wizard, Card::Number(9, blue)],
+                    vec![Card::Wizard, Card::Number(10, yellow), Card::Number(2, red)],
+                ],
+                Some(Card::Number(8, red)),
+                Card::Wizard,
+            ),
+            Lesson::play(
+                "fools",
+                Task::LoseTrick,
+                [
+                    vec![
+                        Card::Number(10, yellow),
+                        Card::Number(5, blue),
+                        Card::Number(1, green),
+                    ],
+                    vec![Card::Fool, Card::Number(12, yellow), Card::Wizard],
+                    vec![
+                        Card::Number(3, yellow),
+                        Card::Number(8, green),
+                        Card::Number(4, blue),
+                    ],
+                ],
+                Some(Card::Number(6, green)),
+                Card::Number(3, yellow),
+            ),
+            Lesson::guess(
+                "bidding",
+                [
+                    vec![
+                        Card::Number(2, green),
+                        Card::Number(7, yellow),
+                        Card::Number(1, blue),
+                        Card::Fool,
+                    ],
+                    vec![
+                        Card::Wizard,
+                        Card::Wizard,
+                        Card::Number(13, red),
+                        Card::Number(2, blue),
+                    ],
+                    vec![
+                        Card::Number(12, red),
+                        Card::Number(9, green),
+                        Card::Number(5, yellow),
+                        Card::Fool,
+                    ],
+                ],
+                Some(Card::Number(3, red)),
+                3,
+            ),
+        ]
+    }
+
+    /// Returns a lesson where Anna has led and the learner has to play the second card of the trick. The title and the
+    /// text are looked up in the message catalog with the key of the lesson.
+    fn play(
+        key: &str,
+        task: Task,
+        hands: [Vec<Card>; 3],
+        trump_card: Option<Card>,
+        reply: Card,
+    ) -> Lesson {
+        let mut round = Lesson::deal(hands, PlayerId(3), trump_card);
+        while round.is_bidding() {
+            round
+                .guess(0)
+                .expect("A guess of 0 is always allowed with 3 cards.");
+        }
+        let lead = round.hands[&PlayerId(1)][0];
+        round.play(lead).expect("Anna may lead any card.");
+        Lesson {
+            title: i18n::text(&format!("tutorial.{}.title", key)),
+            text: i18n::text(&format!("tutorial.{}.text", key)),
+            task,
+            round,
+            replies: vec![reply],
+            explanation: String::new(),
+        }
+    }
+
+    /// Returns a lesson where the learner guesses first.
+    fn guess(key: &str, hands: [Vec<Card>; 3], trump_card: Option<Card>, stitches: u32) -> Lesson {
+        Lesson {
+            title: i18n::text(&format!("tutorial.{}.title", key)),
+            text: i18n::text(&format!("tutorial.{}.text", key)),
+            task: Task::Guess(stitches),
+            round: Lesson::deal(hands, PlayerId(1), trump_card),
+            replies: Vec::new(),
+            explanation: i18n::text(&format!("tutorial.{}.explanation", key)),
+        }
+    }
+
+    /// Deals the scripted hands of Anna, the learner and Ben. Every lesson is played as the first round of its own.
+    fn deal(hands: [Vec<Card>; 3], dealer: PlayerId, trump_card: Option<Card>) -> Round {
+        let main_color = match trump_card {
+            Some(Card::Number(_, color)) => Some(color),
+            _ => None,
+        };
+        let hands = hands
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| (PlayerId(i as u32 + 1), e))
+            .collect();
+        Round::new(1, hands, dealer, trump_card, main_color)
+            .expect("Every lesson deals three hands.")
+    }
+
+    /// Returns the name of a player of the tutorial.
+    pub fn name(player: PlayerId) -> String {
+        match player {
+            PlayerId(1) => "Anna".to_string(),
+            LEARNER => i18n::text("player.you"),
+            _ => "Ben".to_string(),
+        }
+    }
+
+    /// Plays the card of the learner and the replies of the others, then explains who won the trick and why.
+    pub fn try_card(&self, card: Card) -> Feedback {
+        let mut round = self.round.clone();
+        let mut winner = match round.play(card) {
+            Ok(winner) => winner,
+            Err(WizardError::IllegalPlay { playable, .. }) => {
+                let color =
+                    Card::get_prio_color(round.trick.clone()).map_or(String::new(), |e| e.name());
+                let playable: Vec<String> = playable.iter().map(|e| e.name()).collect();
+                return Feedback {
+                    passed: false,
+                    text: i18n::text_with("tutorial.follow", &[&color, &playable.join(", ")]),
+                };
+            }
+            Err(e) => {
+                return Feedback {
+                    passed: false,
+                    text: e.to_string(),
+                }
+            }
+        };
+        for reply in &self.replies {
+            winner = round.play(*reply).expect("The replies are legal.");
+        }
+        let winner = winner.expect("The replies complete the trick.");
+
+        let trick = &round.played[round.played.len() - round.player_count()..];
+        let won = winner == LEARNER;
+        let passed = won == (self.task == Task::WinTrick);
+        let result = if won {
+            i18n::text("tutorial.you_won")
+        } else {
+            i18n::text_with("tutorial.won", &[&Lesson::name(winner)])
+        };
+        let key = if passed {
+            "tutorial.as_planned"
+        } else {
+            "tutorial.not_planned"
+        };
+        Feedback {
+            passed,
+            text: i18n::text_with(key, &[&explain(trick, round.main_color), &result]),
+        }
+    }
+
+    /// Checks the guess of the learner against the tricks the hand is sure to win.
+    pub fn try_guess(&self, stitches: u32) -> Feedback {
+        let Task::Guess(expected) = self.task else {
+            return Feedback {
+                passed: false,
+                text: i18n::text("tutorial.play_a_card"),
+            };
+        };
+        let advice = match stitches.cmp(&expected) {
+            std::cmp::Ordering::Equal => "tutorial.well_guessed",
+            std::cmp::Ordering::Less => "tutorial.too_few",
+            std::cmp::Ordering::Greater => "tutorial.too_many",
+        };
+        Feedback {
+            passed: stitches == expected,
+            text: format!("{} {}", i18n::text(advice), self.explanation),
+        }
+    }
+}
+
+/// Explains with the rules why the trick is won by its winning card.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::card::{Card, CardColor};
+/// # use wizard::wizard::tutorial;
+/// let trick = [Card::Number(7, CardColor::BLUE), Card::Number(2, CardColor::RED), Card::Fool];
+/// assert_eq!(
+///     tutorial::explain(&trick, Some(CardColor::RED)),
+///     "The Red 2 wins, as it is the highest card of the main color Red."
+/// );
+/// ```
+pub fn explain(trick: &[Card], main_color: Option<CardColor>) -> String {
+    let Ok(index) = Card::winning_index(trick.to_vec(), main_color) else {
+        return i18n::text("explain.empty");
+    };
+    match trick[index] {
+        Card::Wizard => i18n::text("explain.wizard"),
+        Card::Fool => i18n::text("explain.fool"),
+        card @ Card::Number(_, color) if Some(color) == main_color => {
+            i18n::text_with("explain.trump", &[&card.name(), &color.name()])
+        }
+        card @ Card::Number(_, color) => {
+            let key = if main_color.is_some() {
+                "explain.color_with_trump"
+            } else {
+                "explain.color"
+            };
+            i18n::text_with(key, &[&card.name(), &color.name()])
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::wizard::card::{Card, CardColor};
+use crate::wizard::game::{Action, Game, Phase};
+use crate::wizard::i18n;
+use crate::wizard::player::{Player, PlayerId};
+use crate::wizard::record::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a single player knows about a game: the public state of the table and their own hand. <br>
+/// Front ends that don't run the game themselves, like network clients, are only given views, so they can't see
+/// the hands of the other players.
+///
+/// # Examples
+///
+/// ```rust
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::game::{Action, Game};
+/// # use wizard::wizard::player::Player;
+/// # use wizard::wizard::view::View;
+/// let mut game = Game::default();
+/// for number in 1..=3 {
+///     game.players.push(Player::bot(Difficulty::Easy, number));
+/// }
+/// game.apply(Action::Start)?;
+/// game.apply(Action::Deal)?;
+///
+/// let first = game.players[0].id;
+/// let view = View::new(&game, Some(first));
+/// assert_eq!(view.hand.len(), 1);
+/// assert_eq!(view.hand, game.round.as_ref().unwrap().hands[&first]);
+/// assert!(View::new(&game, None).hand.is_empty());
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct View {
+    /// The player the view belongs to, `None` for someone who only watches.
+    pub viewer: Option<PlayerId>,
+    pub players: Vec<Player>,
+    pub phase: Phase,
+    pub round_number: u32,
+    pub round_count: u32,
+    /// The amount of cards every player got in the current round.
+    pub cards: u32,
+    pub dealer: Option<PlayerId>,
+    pub trump_card: Option<Card>,
+    pub main_color: Option<CardColor>,
+    pub hand: Vec<Card>,
+    pub guesses: BTreeMap<PlayerId, u32>,
+    pub stitches: BTreeMap<PlayerId, u32>,
+    /// The cards of the current trick and who played them.
+    pub trick: Vec<(PlayerId, Card)>,
+    pub scores: BTreeMap<PlayerId, i32>,
+    /// The player whose turn it is.
+    pub next_player: Option<PlayerId>,
+    /// The actions the viewer can choose from, empty if it's not their turn.
+    pub options: Vec<Action>,
+}
+
+impl View {
+    /// Returns the view of the given player on the game.
+    pub fn new(game: &Game, viewer: Option<PlayerId>) -> Self {
+        let next_player = game.next_player();
+        let mut view = View {
+            viewer,
+            players: game.players.clone(),
+            phase: game.phase,
+            round_number: game.round_number,
+            round_count: game.rounds.len() as u32,
+            cards: 0,
+            dealer: None,
+            trump_card: None,
+            main_color: None,
+            hand: Vec::new(),
+            guesses: BTreeMap::new(),
+            stitches: BTreeMap::new(),
+            trick: Vec::new(),
+            scores: game.scores.clone(),
+            next_player,
+            options: if next_player.is_some() && next_player == viewer {
+                game.legal_actions()
+            } else {
+                Vec::new()
+            },
+        };
+
+        if let Some(round) = &game.round {
+            view.cards = round.cards;
+            view.dealer = Some(round.dealer);
+            view.trump_card = round.trump_card;
+            view.main_color = round.main_color;
+            view.hand = viewer
+                .and_then(|e| round.hands.get(&e))
+                .cloned()
+                .unwrap_or_default();
+            view.guesses = round.guesses.clone();
+            view.stitches = round.stitches.clone();
+            view.trick = round
+                .playing_order()
+                .into_iter()
+                .zip(round.trick.iter().copied())
+                .collect();
+        }
+        view
+    }
+
+    /// Returns the name of the given player.
+    pub fn name(&self, id: PlayerId) -> String {
+        self.players
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| e.name.clone())
+            .unwrap_or_else(|| i18n::text_with("player.unknown", &[&id.0]))
+    }
+
+    /// Returns the event as the given player may see it. The hands of the other players are removed from `RoundStarted`.
+    pub fn redact(event: &Event, viewer: Option<PlayerId>) -> Event {
+        match event {
+            Event::RoundStarted {
+                round,
+                hands,
+                trump_card,
+                main_color,
+            } => Event::RoundStarted {
+                round: *round,
+                hands: hands
+                    .iter()
+                    .map(|(id, hand)| {
+                        let visible = Some(*id) == viewer;
+                        (*id, if visible { hand.clone() } else { Vec::new() })
+                    })
+                    .collect(),
+                trump_card: *trump_card,
+                main_color: *main_color,
+            },
+            event => event.clone(),
+        }
+    }
+}
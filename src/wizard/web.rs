@@ -0,0 +1,112 @@
+use crate::wizard::error::WizardError;
+use crate::wizard::game::Game;
+use crate::wizard::i18n::{self, Language};
+use crate::wizard::net::{self, Connection, Server};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// The single page front end. It connects to `/ws` and speaks the protocol of `net` as JSON text frames.
+pub const PAGE: &str = include_str!("web.html");
+
+/// Hosts a game for players in browser tabs. Every tab that opens the page served at the address of the listener
+/// and joins takes one of the `remote_seats`, see `Server::host`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::net::TcpListener;
+/// # use wizard::wizard::bot::Difficulty;
+/// # use wizard::wizard::game::Game;
+/// # use wizard::wizard::player::Player;
+/// # use wizard::wizard::web;
+/// let mut game = Game::default();
+/// game.players.push(Player::bot(Difficulty::Hard, 1));
+///
+/// // two tabs join, then the game is played
+/// let listener = TcpListener::bind("0.0.0.0:8080")?;
+/// let mut server = web::host(&listener, game, &[1, 2])?;
+/// server.play()?;
+/// # Ok::<(), wizard::wizard::error::WizardError>(())
+/// ```
+pub fn host(
+    listener: &TcpListener,
+    game: Game,
+    remote_seats: &[usize],
+) -> Result<Server, WizardError> {
+    Server::with_clients(game, remote_seats, || accept(listener))
+}
+
+/// Serves the page to every browser that asks for it and returns the next WebSocket connection to `/ws`. Failed page
+/// requests, handshakes and connections are skipped, see `net::connection_failed`. <br>
+/// `WizardError::Network` is returned if the listener fails.
+pub fn accept(listener: &TcpListener) -> Result<(Connection, String), WizardError> {
+    loop {
+        let (stream, address) = match listener.accept() {
+            Ok(client) => client,
+            Err(e) if net::connection_failed(&e) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        // browsers open connections in advance that they may never use
+        if stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .is_err()
+        {
+            continue;
+        }
+
+        let mut buffer = [0; 512];
+        let Ok(length) = stream.peek(&mut buffer) else {
+            continue;
+        };
+        // a failed handshake or page request only concerns that browser
+        if String::from_utf8_lossy(&buffer[..length]).starts_with("GET /ws ") {
+            if stream.set_read_timeout(None).is_err() {
+                continue;
+            }
+            if let Ok(socket) = tungstenite::accept(stream) {
+                return Ok((Connection::web_socket(socket), address.to_string()));
+            }
+        } else {
+            let _ = serve(stream);
+        }
+    }
+}
+
+/// Answers a plain HTTP request with the page or the message catalog at `/locale.json`, or with 404 for anything else.
+fn serve(mut stream: TcpStream) -> Result<(), WizardError> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 512];
+    while !request.windows(4).any(|e| e == b"\r\n\r\n") {
+        let length = stream.read(&mut buffer)?;
+        if length == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..length]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    let locale = locale();
+    let (status, content_type, body) = match path {
+        "/" | "/index.html" => ("200 OK", "text/html; charset=utf-8", PAGE),
+        "/locale.json" => ("200 OK", "application/json; charset=utf-8", locale.as_str()),
+        _ => ("404 Not Found", "text/plain; charset=utf-8", "Not found"),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// Returns the message catalog of the current language as JSON, with the English text of keys it lacks.
+fn locale() -> String {
+    let mut catalog = Language::English.catalog().clone();
+    catalog.extend(i18n::language().catalog().clone());
+    serde_json::to_string(&catalog).unwrap_or_default()
+}